@@ -35,12 +35,12 @@ use serenity_slash_decode::{process, SlashMap};
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
-enum CustomError<'a> {
-    SlashError(SlashError<'a>),
+enum CustomError {
+    SlashError(SlashError),
     CommandNotFound(String),
 }
 
-impl Display for CustomError<'_> {
+impl Display for CustomError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             // serenity-slash-decode's error type implements Display
@@ -50,19 +50,19 @@ impl Display for CustomError<'_> {
     }
 }
 
-impl<'a> From<SlashError<'a>> for CustomError<'a> {
-    fn from(e: SlashError<'a>) -> Self {
+impl From<SlashError> for CustomError {
+    fn from(e: SlashError) -> Self {
         CustomError::SlashError(e)
     }
 }
 
-type CustomResult<'a, T> = Result<T, CustomError<'a>>;
+type CustomResult<T> = Result<T, CustomError>;
 
-async fn handle_command<'a>(
-    ctx: &'a Context,
-    interaction: &'a ApplicationCommandInteraction,
-    args: &'a SlashMap,
-) -> CustomResult<'a, ()> {
+async fn handle_command(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    args: &SlashMap,
+) -> CustomResult<()> {
     let text = args.get_string("text")?;
     let mut message = format!(
         "text: {}\nchannel: {}",
@@ -97,7 +97,7 @@ impl EventHandler for Handler {
         let command = interaction.application_command().unwrap();
         match match path.as_str() {
             "foo" => handle_command(&ctx, &command, &args).await,
-            _ => Err(CustomError::CommandNotFound(path)),
+            _ => Err(CustomError::CommandNotFound(path.to_string())),
         } {
             Ok(_) => {}
             Err(e) => {
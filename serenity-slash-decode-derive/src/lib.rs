@@ -0,0 +1,190 @@
+//! Derive macro for [serenity-slash-decode]'s `FromSlashMap`
+//!
+//! Use it through the `derive` feature of serenity-slash-decode rather than depending on this
+//! crate directly
+//!
+//! [serenity-slash-decode]: https://docs.rs/serenity-slash-decode
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, Data, DeriveInput, Field, Fields, GenericArgument, Lit, Meta, NestedMeta,
+    PathArguments, Type,
+};
+
+/// Derives `FromSlashMap` for a struct with named fields
+///
+/// Each field is read from the argument with the same name, using the getter matching its type:
+/// `String`, `i64`, `bool`, `UserOrMember`, `PartialChannel`, `Role` or `Mentionable`. Wrap a type
+/// in `Option` to make the argument optional. Use `#[slash(rename = "name")]` when the argument
+/// name differs from the field name.
+///
+/// ```
+/// use serenity_slash_decode::{FromSlashMap, UserOrMember};
+///
+/// #[derive(FromSlashMap)]
+/// struct Ban {
+///     user: UserOrMember,
+///     reason: Option<String>,
+///     #[slash(rename = "delete-days")]
+///     delete_days: Option<i64>,
+/// }
+/// ```
+///
+/// Any other field type is a compile error:
+///
+/// ```compile_fail
+/// use serenity_slash_decode::FromSlashMap;
+///
+/// #[derive(FromSlashMap)]
+/// struct Timeout {
+///     seconds: u32,
+/// }
+/// ```
+#[proc_macro_derive(FromSlashMap, attributes(slash))]
+pub fn derive_from_slash_map(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new(
+                    input.ident.span(),
+                    "FromSlashMap can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                "FromSlashMap can only be derived for structs",
+            ))
+        }
+    };
+
+    let fields = fields
+        .iter()
+        .map(expand_field)
+        .collect::<syn::Result<Vec<_>>>()?;
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::serenity_slash_decode::FromSlashMap for #ident #ty_generics #where_clause {
+            // a struct without fields never reads the map
+            #[allow(unused_variables)]
+            fn from_slash_map(
+                map: ::serenity_slash_decode::SlashMap,
+            ) -> ::serenity_slash_decode::Result<Self> {
+                Ok(Self {
+                    #(#fields,)*
+                })
+            }
+        }
+    })
+}
+
+fn expand_field(field: &Field) -> syn::Result<TokenStream2> {
+    let ident = field.ident.as_ref().expect("named fields have idents");
+    let name = option_name(field)?.unwrap_or_else(|| ident.to_string());
+
+    let (ty, optional) = match option_inner(&field.ty) {
+        Some(inner) => (inner, true),
+        None => (&field.ty, false),
+    };
+    let getter = getter_for(ty)?;
+
+    Ok(if optional {
+        quote! {
+            #ident: match map.#getter(#name) {
+                Ok(v) => Some(v),
+                Err(::serenity_slash_decode::Error::MissingValue { .. }) => None,
+                Err(e) => return Err(e),
+            }
+        }
+    } else {
+        quote! {
+            #ident: map.#getter(#name)?
+        }
+    })
+}
+
+/// Reads `#[slash(rename = "...")]` off a field
+fn option_name(field: &Field) -> syn::Result<Option<String>> {
+    let mut name = None;
+    for attr in field.attrs.iter().filter(|a| a.path.is_ident("slash")) {
+        let list = match attr.parse_meta()? {
+            Meta::List(list) => list,
+            meta => return Err(syn::Error::new(meta.span(), "expected #[slash(...)]")),
+        };
+        for nested in list.nested {
+            match nested {
+                NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("rename") => {
+                    match nv.lit {
+                        Lit::Str(s) => name = Some(s.value()),
+                        lit => return Err(syn::Error::new(lit.span(), "expected a string")),
+                    }
+                }
+                nested => {
+                    return Err(syn::Error::new(
+                        nested.span(),
+                        "unknown slash attribute, expected `rename`",
+                    ))
+                }
+            }
+        }
+    }
+    Ok(name)
+}
+
+/// Returns `T` if `ty` is `Option<T>`
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let segment = match ty {
+        Type::Path(path) if path.qself.is_none() => path.path.segments.last()?,
+        _ => return None,
+    };
+    if segment.ident != "Option" {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 1 => match &args.args[0] {
+            GenericArgument::Type(inner) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Picks the `SlashMap` getter for a field type by its last path segment
+fn getter_for(ty: &Type) -> syn::Result<TokenStream2> {
+    let ident = match ty {
+        Type::Path(path) if path.qself.is_none() => path.path.segments.last().map(|s| &s.ident),
+        _ => None,
+    };
+    let getter = match ident.map(|i| i.to_string()).as_deref() {
+        Some("String") => quote!(get_string),
+        Some("i64") => quote!(get_integer),
+        Some("bool") => quote!(get_boolean),
+        Some("UserOrMember") => quote!(get_user),
+        Some("PartialChannel") => quote!(get_channel),
+        Some("Role") => quote!(get_role),
+        Some("Mentionable") => quote!(get_mentionable),
+        _ => {
+            return Err(syn::Error::new(
+                ty.span(),
+                "unsupported field type, expected one of String, i64, bool, UserOrMember, \
+                 PartialChannel, Role or Mentionable (optionally wrapped in Option)",
+            ))
+        }
+    };
+    Ok(getter)
+}
@@ -0,0 +1,58 @@
+use serenity::model::interactions::application_command::ApplicationCommandInteractionDataOptionValue;
+use std::collections::HashMap;
+
+/// Supplies fallback values for arguments that weren't given by the user
+///
+/// Implement this on whatever holds your per-guild configuration, then use the `SlashMap`
+/// `get_*_or_default` getters to fall back to it
+pub trait DefaultsProvider {
+    /// Returns the default value for the argument `name`, if one is configured
+    fn get_default(&self, name: &str) -> Option<ApplicationCommandInteractionDataOptionValue>;
+}
+
+/// A `DefaultsProvider` backed by a fixed set of values, e.g. loaded from a config file at startup
+#[derive(Debug, Default, Clone)]
+pub struct StaticDefaults(HashMap<String, ApplicationCommandInteractionDataOptionValue>);
+
+impl StaticDefaults {
+    /// Creates an empty set of defaults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a default value for the argument `name`
+    pub fn with(mut self, name: &str, value: ApplicationCommandInteractionDataOptionValue) -> Self {
+        self.0.insert(name.to_string(), value);
+        self
+    }
+
+    /// Reads defaults from environment variables starting with `prefix`
+    ///
+    /// `{prefix}DURATION=10m` becomes a default for the `duration` argument. Values are kept as
+    /// `String`s; `get_integer_or_default` and `get_boolean_or_default` parse them when used, so
+    /// `{prefix}CODE=1234` still works as the default for a string argument. Variables whose name
+    /// or value isn't valid Unicode are skipped
+    pub fn from_env(prefix: &str) -> Self {
+        let mut defaults = Self::new();
+        // env::vars() panics on the first non-Unicode variable, even one without the prefix
+        for (key, value) in std::env::vars_os() {
+            let (key, value) = match (key.into_string(), value.into_string()) {
+                (Ok(key), Ok(value)) => (key, value),
+                _ => continue,
+            };
+            if let Some(name) = key.strip_prefix(prefix) {
+                defaults.0.insert(
+                    name.to_lowercase(),
+                    ApplicationCommandInteractionDataOptionValue::String(value),
+                );
+            }
+        }
+        defaults
+    }
+}
+
+impl DefaultsProvider for StaticDefaults {
+    fn get_default(&self, name: &str) -> Option<ApplicationCommandInteractionDataOptionValue> {
+        self.0.get(name).cloned()
+    }
+}
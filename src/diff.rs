@@ -0,0 +1,62 @@
+use crate::{SlashMap, SlashValue};
+use serenity::model::interactions::application_command::ApplicationCommandInteractionDataOptionValue as Value;
+
+/// Differences between two `SlashMap`s, each sorted by argument name
+///
+/// Created by `SlashMap::diff`
+#[derive(Debug)]
+pub struct SlashMapDiff<'a> {
+    /// Arguments only in the new map
+    pub added: Vec<(&'a str, &'a SlashValue)>,
+    /// Arguments only in the old map
+    pub removed: Vec<(&'a str, &'a SlashValue)>,
+    /// Arguments in both maps with different values, as `(name, old, new)`
+    pub changed: Vec<(&'a str, &'a SlashValue, &'a SlashValue)>,
+}
+
+impl SlashMapDiff<'_> {
+    /// Checks if the maps had the same arguments and values
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compares values, treating users, channels and roles as equal if their IDs are
+fn same_value(a: &SlashValue, b: &SlashValue) -> bool {
+    match (&a.inner, &b.inner) {
+        (Some(Value::String(a)), Some(Value::String(b))) => a == b,
+        (Some(Value::Integer(a)), Some(Value::Integer(b))) => a == b,
+        (Some(Value::Boolean(a)), Some(Value::Boolean(b))) => a == b,
+        (Some(Value::User(a, _)), Some(Value::User(b, _))) => a.id == b.id,
+        (Some(Value::Channel(a)), Some(Value::Channel(b))) => a.id == b.id,
+        (Some(Value::Role(a)), Some(Value::Role(b))) => a.id == b.id,
+        (None, None) => a.raw == b.raw,
+        _ => false,
+    }
+}
+
+pub(crate) fn diff<'a>(old: &'a SlashMap, new: &'a SlashMap) -> SlashMapDiff<'a> {
+    let mut diff = SlashMapDiff {
+        added: Vec::new(),
+        removed: Vec::new(),
+        changed: Vec::new(),
+    };
+    for (name, old_value) in &old.0 {
+        match new.0.get(name) {
+            Some(new_value) if !same_value(old_value, new_value) => {
+                diff.changed.push((name, old_value, new_value))
+            }
+            Some(_) => {}
+            None => diff.removed.push((name, old_value)),
+        }
+    }
+    for (name, new_value) in &new.0 {
+        if !old.0.contains_key(name) {
+            diff.added.push((name, new_value));
+        }
+    }
+    diff.added.sort_by_key(|e| e.0);
+    diff.removed.sort_by_key(|e| e.0);
+    diff.changed.sort_by_key(|e| e.0);
+    diff
+}
@@ -1,35 +1,306 @@
-use std::fmt::{Display, Formatter};
+use serenity::model::channel::ChannelType;
+use std::fmt::{Display, Formatter, Write};
 
 #[derive(Debug)]
-pub enum Error<'a> {
+pub enum Error {
     WrongType {
+        expected: &'static str,
+        found: &'static str,
+        name: String,
+    },
+    MissingValue {
+        name: String,
+    },
+    ChannelNotInGuild {
+        name: String,
+    },
+    BotUser {
+        name: String,
+    },
+    SelfTarget {
+        name: String,
+    },
+    ParseFailed {
+        name: String,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    PatternMismatch {
+        name: String,
+        pattern: String,
+    },
+    OutOfRange {
+        name: String,
+        min: i64,
+        max: i64,
+    },
+    LengthOutOfRange {
+        name: String,
+        min: usize,
+        max: usize,
+    },
+    WrongChannelType {
+        name: String,
+        found: ChannelType,
+    },
+    /// `kind` is the option type's number, if it could be recovered
+    UnsupportedType {
+        name: String,
+        kind: Option<u8>,
+    },
+    NotOneOf {
+        name: String,
+        allowed: &'static [&'static str],
+    },
+    PathMismatch {
         expected: String,
         found: String,
-        name: &'a str,
     },
-    MissingValue {
-        name: &'a str,
+    PayloadLimitExceeded {
+        limit: &'static str,
     },
+    InvalidPayload(Box<dyn std::error::Error + Send + Sync>),
+    Serenity(serenity::Error),
 }
 
-impl Display for Error<'_> {
+impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.render(f, false)
+    }
+}
+
+/// Escapes text on its way to a `Formatter` so it can't break out of, or inject, Discord markdown
+pub(crate) struct DiscordEscaper<'a, 'b> {
+    pub(crate) f: &'a mut Formatter<'b>,
+    /// Whether the text ends up inside a code span, where only backticks are special
+    pub(crate) code: bool,
+}
+
+impl Write for DiscordEscaper<'_, '_> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        for c in s.chars() {
+            match c {
+                // backslashes don't escape inside code spans, so swap in a lookalike
+                '`' if self.code => self.f.write_char('\u{2035}')?,
+                // brackets and parentheses too, so masked links like [text](url) can't form
+                '`' | '*' | '_' | '~' | '|' | '>' | '\\' | '[' | ']' | '(' | ')' if !self.code => {
+                    self.f.write_char('\\')?;
+                    self.f.write_char(c)?;
+                }
+                // break up @everyone and friends
+                '@' => self.f.write_str("@\u{200b}")?,
+                '\n' | '\r' => self.f.write_char(' ')?,
+                c => self.f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes user-supplied text, escaping it if it is going to Discord
+fn user_text(
+    f: &mut Formatter<'_>,
+    discord: bool,
+    code: bool,
+    text: impl Display,
+) -> std::fmt::Result {
+    if discord {
+        write!(DiscordEscaper { f, code }, "{}", text)
+    } else {
+        write!(f, "{}", text)
+    }
+}
+
+struct DiscordString<'a>(&'a Error);
+
+impl Display for DiscordString<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.render(f, true)
+    }
+}
+
+impl Error {
+    fn render(&self, f: &mut Formatter<'_>, discord: bool) -> std::fmt::Result {
         match self {
             Error::WrongType {
                 expected,
                 found,
                 name,
-            } => f.write_str(&*format!(
-                "Wrong type in field `{}` (expected `{}`, got `{}`)",
-                name, expected, found
-            )),
+            } => {
+                f.write_str("Wrong type in field `")?;
+                user_text(f, discord, true, name)?;
+                write!(f, "` (expected `{}`, got `{}`)", expected, found)
+            }
             Error::MissingValue { name } => {
-                f.write_str(&*format!("Missing value in field `{}`", name))
+                f.write_str("Missing value in field `")?;
+                user_text(f, discord, true, name)?;
+                f.write_str("`")
+            }
+            Error::ChannelNotInGuild { name } => {
+                f.write_str("Channel in field `")?;
+                user_text(f, discord, true, name)?;
+                f.write_str("` is not part of this server")
+            }
+            Error::BotUser { name } => {
+                f.write_str("User in field `")?;
+                user_text(f, discord, true, name)?;
+                f.write_str("` can't be a bot")
+            }
+            Error::SelfTarget { name } => {
+                f.write_str("User in field `")?;
+                user_text(f, discord, true, name)?;
+                f.write_str("` can't be yourself")
+            }
+            Error::ParseFailed { name, source } => {
+                f.write_str("Couldn't parse field `")?;
+                user_text(f, discord, true, name)?;
+                f.write_str("`: ")?;
+                user_text(f, discord, false, source)
             }
+            Error::PatternMismatch { name, pattern } => {
+                f.write_str("Value in field `")?;
+                user_text(f, discord, true, name)?;
+                f.write_str("` doesn't match the format `")?;
+                user_text(f, discord, true, pattern)?;
+                f.write_str("`")
+            }
+            Error::OutOfRange { name, min, max } => {
+                f.write_str("Value in field `")?;
+                user_text(f, discord, true, name)?;
+                write!(f, "` must be between {} and {}", min, max)
+            }
+            Error::LengthOutOfRange { name, min, max } => {
+                f.write_str("Value in field `")?;
+                user_text(f, discord, true, name)?;
+                write!(f, "` must be between {} and {} characters long", min, max)
+            }
+            Error::WrongChannelType { name, found } => {
+                f.write_str("Channel in field `")?;
+                user_text(f, discord, true, name)?;
+                write!(f, "` can't be a {:?} channel", found)
+            }
+            Error::UnsupportedType { name, kind } => {
+                f.write_str("Field `")?;
+                user_text(f, discord, true, name)?;
+                f.write_str("` has an unsupported option type")?;
+                match kind {
+                    Some(kind) => write!(f, " ({})", kind),
+                    None => Ok(()),
+                }
+            }
+            Error::NotOneOf { name, allowed } => {
+                f.write_str("Value in field `")?;
+                user_text(f, discord, true, name)?;
+                f.write_str("` must be one of ")?;
+                for (i, choice) in allowed.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "`{}`", choice)?;
+                }
+                Ok(())
+            }
+            Error::PathMismatch { expected, found } => {
+                f.write_str("Expected command `")?;
+                user_text(f, discord, true, expected)?;
+                f.write_str("`, got `")?;
+                user_text(f, discord, true, found)?;
+                f.write_str("`")
+            }
+            Error::PayloadLimitExceeded { limit } => {
+                write!(f, "Interaction payload exceeds the {} limit", limit)
+            }
+            Error::InvalidPayload(e) => {
+                f.write_str("Invalid interaction payload: ")?;
+                user_text(f, discord, false, e)
+            }
+            Error::Serenity(e) => user_text(f, discord, false, e),
         }
     }
+
+    /// Renders the error for sending back to Discord, escaping field names and values so they
+    /// can't break or abuse the message's markdown
+    pub fn to_discord_string(&self) -> String {
+        DiscordString(self).to_string()
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::ParseFailed { source, .. } => Some(&**source),
+            Error::InvalidPayload(e) => Some(&**e),
+            Error::Serenity(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<serenity::Error> for Error {
+    fn from(e: serenity::Error) -> Self {
+        Error::Serenity(e)
+    }
 }
 
-impl std::error::Error for Error<'_> {}
+pub type Result<T> = std::result::Result<T, Error>;
 
-pub type Result<'a, T> = std::result::Result<T, Error<'a>>;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Escaped<'a> {
+        text: &'a str,
+        code: bool,
+    }
+
+    impl Display for Escaped<'_> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            DiscordEscaper { f, code: self.code }.write_str(self.text)
+        }
+    }
+
+    fn escape(text: &str, code: bool) -> String {
+        Escaped { text, code }.to_string()
+    }
+
+    #[test]
+    fn escapes_markdown() {
+        assert_eq!(escape("**bold** _it_", false), r"\*\*bold\*\* \_it\_");
+        assert_eq!(
+            escape("~~s~~ ||spoiler|| > quote", false),
+            r"\~\~s\~\~ \|\|spoiler\|\| \> quote"
+        );
+        assert_eq!(escape(r"a\b `c`", false), r"a\\b \`c\`");
+    }
+
+    #[test]
+    fn escapes_masked_links() {
+        assert_eq!(
+            escape("[free nitro](https://example.com)", false),
+            r"\[free nitro\]\(https://example.com\)"
+        );
+    }
+
+    #[test]
+    fn code_spans_only_swap_backticks() {
+        assert_eq!(escape("a`b *c* [d](e)", true), "a\u{2035}b *c* [d](e)");
+    }
+
+    #[test]
+    fn breaks_mass_mentions_and_newlines() {
+        assert_eq!(escape("@everyone", false), "@\u{200b}everyone");
+        assert_eq!(escape("@here", true), "@\u{200b}here");
+        assert_eq!(escape("a\nb\rc", false), "a b c");
+    }
+
+    #[test]
+    fn discord_string_escapes_names() {
+        let e = Error::MissingValue {
+            name: "`@everyone`".to_string(),
+        };
+        assert_eq!(e.to_string(), "Missing value in field ``@everyone``");
+        assert_eq!(
+            e.to_discord_string(),
+            "Missing value in field `\u{2035}@\u{200b}everyone\u{2035}`"
+        );
+    }
+}
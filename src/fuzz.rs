@@ -0,0 +1,45 @@
+//! Entry points for running the crate under [cargo-fuzz]
+//!
+//! [cargo-fuzz]: https://github.com/rust-fuzz/cargo-fuzz
+
+use crate::process_json;
+use chrono::Utc;
+
+/// Decodes arbitrary bytes and exercises every getter on the result
+///
+/// Any panic reached from here is a bug. Call it from a `fuzz_target!`:
+///
+/// ```ignore
+/// fuzz_target!(|data: &[u8]| serenity_slash_decode::fuzz::fuzz_process(data));
+/// ```
+pub fn fuzz_process(data: &[u8]) {
+    let (path, args) = match process_json(data) {
+        Ok(s) => s,
+        Err(e) => {
+            let _ = e.to_discord_string();
+            return;
+        }
+    };
+    let _ = (path.depth(), path.parent(), path.leaf());
+    for (name, _, _) in args.to_fields() {
+        let _ = args.get_string(&name).map_err(|e| e.to_discord_string());
+        let _ = args.get_integer(&name);
+        let _ = args.get_boolean(&name);
+        let _ = args.get_user(&name);
+        let _ = args.get_channel(&name);
+        let _ = args.get_role(&name);
+        let _ = args.get_mentionable(&name);
+        let _ = args.get_bytes(&name);
+        let _ = args.get_percent(&name);
+        let _ = args.get_time_of_day(&name, &Utc);
+        let _ = args.get_message_link(&name);
+        let _ = args.get_invite(&name);
+        let _ = args.get_math_expr(&name);
+        let _ = args.get_math_expr_int(&name);
+        let _ = args.get_user_list(&name);
+        #[cfg(feature = "cron")]
+        let _ = args.get_cron(&name);
+        #[cfg(feature = "semver")]
+        let _ = (args.get_semver(&name), args.get_semver_req(&name));
+    }
+}
@@ -8,18 +8,45 @@
 //!
 //! [Serenity]: https://docs.rs/serenity/latest/serenity/
 
+mod defaults;
+mod diff;
 mod errors;
+#[cfg(feature = "fuzz")]
+pub mod fuzz;
+mod limits;
+mod moderation;
+mod parse;
+mod path;
+mod redact;
+mod timezone;
 
+pub use crate::defaults::{DefaultsProvider, StaticDefaults};
+pub use crate::diff::SlashMapDiff;
+use crate::errors::DiscordEscaper;
 pub use crate::errors::{Error, Result};
-use serenity::model::channel::PartialChannel;
+pub use crate::limits::PayloadLimits;
+pub use crate::moderation::{can_moderate, ModerationAction, ModerationContext, ModerationRefusal};
+pub use crate::parse::{MessageLink, ParseError};
+pub use crate::path::{path_segments, CommandPath, PathSegments};
+pub use crate::redact::Redacted;
+pub use crate::timezone::TimezoneProvider;
+use chrono::{DateTime, FixedOffset, Utc};
+use serenity::model::channel::{ChannelType, PartialChannel};
 use serenity::model::guild::{PartialMember, Role};
+use serenity::model::id::{ChannelId, UserId};
 use serenity::model::interactions::application_command::{
     ApplicationCommandInteractionData, ApplicationCommandInteractionDataOptionValue,
     ApplicationCommandOptionType,
 };
 use serenity::model::misc::{Mention, Mentionable as SerenityMentionable};
 use serenity::model::user::User;
+#[cfg(feature = "derive")]
+pub use serenity_slash_decode_derive::FromSlashMap;
 use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter, Write};
+use std::ops::RangeInclusive;
+use std::str::FromStr;
 
 /// Contains the values of the slash command
 #[derive(Debug)]
@@ -28,6 +55,13 @@ pub struct SlashValue {
     inner: Option<ApplicationCommandInteractionDataOptionValue>,
     /// The name of the parameter; Included for error messages
     name: String,
+    /// The option type Discord sent
+    kind: ApplicationCommandOptionType,
+    /// The option type's number, or `None` if serenity didn't know the type and the number
+    /// couldn't be recovered from the payload
+    type_number: Option<u8>,
+    /// The unresolved value Discord sent, kept for option types serenity can't resolve
+    raw: Option<serde_json::Value>,
 }
 
 /// Optionally contains a `PartialMember` so you don't need to do a cache lookup
@@ -77,58 +111,289 @@ impl SerenityMentionable for Mentionable {
 }
 
 impl SlashValue {
-    fn get_type_name(&self) -> String {
-        match self.inner.as_ref().unwrap() {
-            ApplicationCommandInteractionDataOptionValue::String(_) => "String".to_string(),
-            ApplicationCommandInteractionDataOptionValue::Integer(_) => "Integer".to_string(),
-            ApplicationCommandInteractionDataOptionValue::Boolean(_) => "Boolean".to_string(),
-            ApplicationCommandInteractionDataOptionValue::User(_, _) => "User".to_string(),
-            ApplicationCommandInteractionDataOptionValue::Channel(_) => "Channel".to_string(),
-            ApplicationCommandInteractionDataOptionValue::Role(_) => "Role".to_string(),
-            _ => "Unknown".to_string(),
+    fn from_value(name: &str, value: ApplicationCommandInteractionDataOptionValue) -> Self {
+        let kind = match &value {
+            ApplicationCommandInteractionDataOptionValue::String(_) => {
+                ApplicationCommandOptionType::String
+            }
+            ApplicationCommandInteractionDataOptionValue::Integer(_) => {
+                ApplicationCommandOptionType::Integer
+            }
+            ApplicationCommandInteractionDataOptionValue::Boolean(_) => {
+                ApplicationCommandOptionType::Boolean
+            }
+            ApplicationCommandInteractionDataOptionValue::User(_, _) => {
+                ApplicationCommandOptionType::User
+            }
+            ApplicationCommandInteractionDataOptionValue::Channel(_) => {
+                ApplicationCommandOptionType::Channel
+            }
+            ApplicationCommandInteractionDataOptionValue::Role(_) => {
+                ApplicationCommandOptionType::Role
+            }
+            _ => ApplicationCommandOptionType::Unknown,
+        };
+        Self {
+            inner: Some(value),
+            name: name.to_string(),
+            kind,
+            type_number: type_number(kind),
+            raw: None,
+        }
+    }
+
+    /// Returns the option type Discord sent
+    pub fn kind(&self) -> ApplicationCommandOptionType {
+        self.kind
+    }
+
+    /// Returns the unresolved value Discord sent, for option types this crate doesn't support yet
+    pub fn raw(&self) -> Option<&serde_json::Value> {
+        self.raw.as_ref()
+    }
+
+    fn get_type_name(&self) -> &'static str {
+        match &self.inner {
+            Some(ApplicationCommandInteractionDataOptionValue::String(_)) => "String",
+            Some(ApplicationCommandInteractionDataOptionValue::Integer(_)) => "Integer",
+            Some(ApplicationCommandInteractionDataOptionValue::Boolean(_)) => "Boolean",
+            Some(ApplicationCommandInteractionDataOptionValue::User(_, _)) => "User",
+            Some(ApplicationCommandInteractionDataOptionValue::Channel(_)) => "Channel",
+            Some(ApplicationCommandInteractionDataOptionValue::Role(_)) => "Role",
+            _ => "Unknown",
         }
     }
 
+    /// Whether the user gave a value, resolved or not
+    fn is_present(&self) -> bool {
+        self.inner.is_some() || self.raw.is_some()
+    }
+
     /// Returns the inner value if it is `Some`
     pub fn expect_some(&self) -> Result<ApplicationCommandInteractionDataOptionValue> {
         match &self.inner {
             Some(s) => Ok(s.to_owned()),
-            None => Err(Error::MissingValue { name: &self.name }),
+            None if self.raw.is_some() => Err(Error::UnsupportedType {
+                name: self.name.clone(),
+                kind: self.type_number,
+            }),
+            None => Err(Error::MissingValue {
+                name: self.name.clone(),
+            }),
         }
     }
 
     /// Returns the inner value if it is a `String`
-    pub fn get_string(&self) -> Result<'_, String> {
+    pub fn get_string(&self) -> Result<String> {
         match self.expect_some()? {
             ApplicationCommandInteractionDataOptionValue::String(s) => Ok(s),
             _ => Err(Error::WrongType {
-                expected: "String".to_string(),
+                expected: "String",
                 found: self.get_type_name(),
-                name: &self.name,
+                name: self.name.clone(),
             }),
         }
     }
 
+    /// Returns the inner value if it is a `String` matching `pattern`
+    #[cfg(feature = "regex")]
+    pub fn get_string_matching(&self, pattern: &regex::Regex) -> Result<String> {
+        let s = self.get_string()?;
+        if pattern.is_match(&s) {
+            Ok(s)
+        } else {
+            Err(Error::PatternMismatch {
+                name: self.name.clone(),
+                pattern: pattern.as_str().to_string(),
+            })
+        }
+    }
+
+    /// Returns the inner value if it is a `String` with a length in `range`, counted in characters
+    pub fn get_string_with_length(&self, range: RangeInclusive<usize>) -> Result<String> {
+        let s = self.get_string()?;
+        if range.contains(&s.chars().count()) {
+            Ok(s)
+        } else {
+            Err(Error::LengthOutOfRange {
+                name: self.name.clone(),
+                min: *range.start(),
+                max: *range.end(),
+            })
+        }
+    }
+
+    /// Returns the matching entry of `choices` if the inner value is a `String` in it
+    pub fn get_string_one_of(&self, choices: &'static [&'static str]) -> Result<&'static str> {
+        let s = self.get_string()?;
+        match choices.iter().find(|c| **c == s) {
+            Some(c) => Ok(c),
+            None => Err(Error::NotOneOf {
+                name: self.name.clone(),
+                allowed: choices,
+            }),
+        }
+    }
+
+    fn parse_with<T>(&self, parser: fn(&str) -> parse::ParseResult<T>) -> Result<T> {
+        parser(&self.get_string()?).map_err(|e| Error::ParseFailed {
+            name: self.name.clone(),
+            source: Box::new(e),
+        })
+    }
+
+    /// Returns the inner value as a number of bytes if it is an `Integer` or a `String` like
+    /// `10MB` or `512KiB`
+    pub fn get_bytes(&self) -> Result<u64> {
+        match self.expect_some()? {
+            ApplicationCommandInteractionDataOptionValue::Integer(i) if i >= 0 => Ok(i as u64),
+            ApplicationCommandInteractionDataOptionValue::Integer(_) => Err(Error::ParseFailed {
+                name: self.name.clone(),
+                source: "size can't be negative".into(),
+            }),
+            _ => self.parse_with(parse::parse_bytes),
+        }
+    }
+
+    /// Returns the inner value as a fraction if it is an `Integer` percentage or a `String` like
+    /// `35%`, `35` or `0.35`
+    ///
+    /// Whole numbers are percentages on both paths, so `35` is `0.35` either way, while a bare
+    /// decimal like `0.35` is already a fraction. Values outside 0% to 100% are rejected
+    pub fn get_percent(&self) -> Result<f64> {
+        match self.expect_some()? {
+            ApplicationCommandInteractionDataOptionValue::Integer(i) => {
+                parse::percent_to_fraction(i as f64).map_err(|e| Error::ParseFailed {
+                    name: self.name.clone(),
+                    source: Box::new(e),
+                })
+            }
+            _ => self.parse_with(parse::parse_percent),
+        }
+    }
+
+    /// Returns the next occurrence of the time of day in the inner value, if it is a `String` like
+    /// `18:30` or `6pm`, interpreted in the timezone from `tz`
+    pub fn get_time_of_day(&self, tz: &impl TimezoneProvider) -> Result<DateTime<FixedOffset>> {
+        let time = self.parse_with(parse::parse_time_of_day)?;
+        timezone::next_time_of_day(time, Utc::now(), tz).ok_or_else(|| Error::ParseFailed {
+            name: self.name.clone(),
+            source: "that time doesn't exist in this timezone".into(),
+        })
+    }
+
+    /// Returns the inner value parsed as a cron schedule if it is a `String`
+    ///
+    /// Expressions start with a seconds field, e.g. `0 30 9 * * Mon-Fri`
+    #[cfg(feature = "cron")]
+    pub fn get_cron(&self) -> Result<cron::Schedule> {
+        self.get_parsed()
+    }
+
+    /// Returns the inner value parsed as a message link if it is a `String`
+    pub fn get_message_link(&self) -> Result<MessageLink> {
+        self.parse_with(parse::parse_message_link)
+    }
+
+    /// Returns the invite code in the inner value if it is a `String` with a code or invite link
+    pub fn get_invite(&self) -> Result<String> {
+        self.parse_with(parse::parse_invite)
+    }
+
+    /// Returns the inner value parsed as a semantic version if it is a `String`
+    #[cfg(feature = "semver")]
+    pub fn get_semver(&self) -> Result<semver::Version> {
+        self.get_parsed()
+    }
+
+    /// Returns the inner value parsed as a semantic version requirement (e.g. `^1.2`) if it is a
+    /// `String`
+    #[cfg(feature = "semver")]
+    pub fn get_semver_req(&self) -> Result<semver::VersionReq> {
+        self.get_parsed()
+    }
+
+    /// Returns the result of the arithmetic expression in the inner value if it is a `String` like
+    /// `2*10^6`, `5k` or `1.5m`
+    pub fn get_math_expr(&self) -> Result<f64> {
+        self.parse_with(parse::parse_math_expr)
+    }
+
+    /// Like `get_math_expr()`, but requires a whole number that fits in an `i64`
+    pub fn get_math_expr_int(&self) -> Result<i64> {
+        self.parse_with(parse::parse_math_expr_int)
+    }
+
+    /// Returns the user IDs in the inner value if it is a `String` of mentions and/or IDs
+    /// separated by spaces or commas
+    pub fn get_user_list(&self) -> Result<Vec<UserId>> {
+        self.parse_with(parse::parse_user_list)
+    }
+
+    /// Like `get_integer()`, but also parses a `String`, for defaults that were read from text
+    fn get_integer_from_text(&self) -> Result<i64> {
+        match &self.inner {
+            Some(ApplicationCommandInteractionDataOptionValue::String(_)) => self.get_parsed(),
+            _ => self.get_integer(),
+        }
+    }
+
+    /// Like `get_boolean()`, but also parses a `String`, for defaults that were read from text
+    fn get_boolean_from_text(&self) -> Result<bool> {
+        match &self.inner {
+            Some(ApplicationCommandInteractionDataOptionValue::String(_)) => self.get_parsed(),
+            _ => self.get_boolean(),
+        }
+    }
+
+    /// Returns the inner value parsed with `FromStr` if it is a `String`
+    pub fn get_parsed<T>(&self) -> Result<T>
+    where
+        T: FromStr,
+        T::Err: std::error::Error + Send + Sync + 'static,
+    {
+        self.get_string()?
+            .parse()
+            .map_err(|e: T::Err| Error::ParseFailed {
+                name: self.name.clone(),
+                source: Box::new(e),
+            })
+    }
+
     /// Returns the inner value if it is an `Integer`
     pub fn get_integer(&self) -> Result<i64> {
         match self.expect_some()? {
             ApplicationCommandInteractionDataOptionValue::Integer(s) => Ok(s),
             _ => Err(Error::WrongType {
-                expected: "Integer".to_string(),
+                expected: "Integer",
                 found: self.get_type_name(),
-                name: &self.name,
+                name: self.name.clone(),
             }),
         }
     }
 
+    /// Returns the inner value if it is an `Integer` in `range`
+    pub fn get_integer_in(&self, range: RangeInclusive<i64>) -> Result<i64> {
+        let i = self.get_integer()?;
+        if range.contains(&i) {
+            Ok(i)
+        } else {
+            Err(Error::OutOfRange {
+                name: self.name.clone(),
+                min: *range.start(),
+                max: *range.end(),
+            })
+        }
+    }
+
     /// Returns the inner value if it is a `Boolean`
     pub fn get_boolean(&self) -> Result<bool> {
         match self.expect_some()? {
             ApplicationCommandInteractionDataOptionValue::Boolean(s) => Ok(s),
             _ => Err(Error::WrongType {
-                expected: "Boolean".to_string(),
+                expected: "Boolean",
                 found: self.get_type_name(),
-                name: &self.name,
+                name: self.name.clone(),
             }),
         }
     }
@@ -140,33 +405,95 @@ impl SlashValue {
                 Ok(UserOrMember::from_pair(u, m))
             }
             _ => Err(Error::WrongType {
-                expected: "User".to_string(),
+                expected: "User",
                 found: self.get_type_name(),
-                name: &self.name,
+                name: self.name.clone(),
             }),
         }
     }
 
+    /// Returns the inner value if it is a `UserOrMember` that isn't a bot
+    pub fn get_human_user(&self) -> Result<UserOrMember> {
+        let user = self.get_user()?;
+        if user.get_user().bot {
+            Err(Error::BotUser {
+                name: self.name.clone(),
+            })
+        } else {
+            Ok(user)
+        }
+    }
+
+    /// Returns the inner value if it is a `UserOrMember` other than `invoker`
+    pub fn get_other_user(&self, invoker: UserId) -> Result<UserOrMember> {
+        let user = self.get_user()?;
+        if user.get_user().id == invoker {
+            Err(Error::SelfTarget {
+                name: self.name.clone(),
+            })
+        } else {
+            Ok(user)
+        }
+    }
+
     /// Returns the inner value if it is a `PartialChannel`
     pub fn get_channel(&self) -> Result<PartialChannel> {
         match self.expect_some()? {
             ApplicationCommandInteractionDataOptionValue::Channel(s) => Ok(s),
             _ => Err(Error::WrongType {
-                expected: "Channel".to_string(),
+                expected: "Channel",
                 found: self.get_type_name(),
-                name: &self.name,
+                name: self.name.clone(),
             }),
         }
     }
 
+    /// Returns the inner value if it is a `PartialChannel` that `in_guild` says belongs to the
+    /// guild
+    ///
+    /// Serenity keeps active threads in `Guild::threads` rather than `Guild::channels`, so check
+    /// both if threads should be accepted:
+    ///
+    /// ```ignore
+    /// value.get_guild_channel(|id| {
+    ///     guild.channels.contains_key(&id) || guild.threads.iter().any(|t| t.id == id)
+    /// })?;
+    /// ```
+    pub fn get_guild_channel(
+        &self,
+        in_guild: impl Fn(ChannelId) -> bool,
+    ) -> Result<PartialChannel> {
+        let channel = self.get_channel()?;
+        if in_guild(channel.id) {
+            Ok(channel)
+        } else {
+            Err(Error::ChannelNotInGuild {
+                name: self.name.clone(),
+            })
+        }
+    }
+
+    /// Returns the inner value if it is a `PartialChannel` of one of the `kinds`
+    pub fn get_channel_of_kind(&self, kinds: &[ChannelType]) -> Result<PartialChannel> {
+        let channel = self.get_channel()?;
+        if kinds.contains(&channel.kind) {
+            Ok(channel)
+        } else {
+            Err(Error::WrongChannelType {
+                name: self.name.clone(),
+                found: channel.kind,
+            })
+        }
+    }
+
     /// Returns the inner value if it is a `Role`
     pub fn get_role(&self) -> Result<Role> {
         match self.expect_some()? {
             ApplicationCommandInteractionDataOptionValue::Role(s) => Ok(s),
             _ => Err(Error::WrongType {
-                expected: "Role".to_string(),
+                expected: "Role",
                 found: self.get_type_name(),
-                name: &self.name,
+                name: self.name.clone(),
             }),
         }
     }
@@ -179,121 +506,738 @@ impl SlashValue {
             }
             ApplicationCommandInteractionDataOptionValue::Role(r) => Ok(Mentionable::Role(r)),
             _ => Err(Error::WrongType {
-                expected: "Mentionable".to_string(),
+                expected: "Mentionable",
                 found: self.get_type_name(),
-                name: &self.name,
+                name: self.name.clone(),
             }),
         }
     }
 }
 
+/// Renders the value for display in Discord, with users, channels and roles as mentions and
+/// markdown in strings escaped
+impl Display for SlashValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.inner {
+            None => f.write_str("*unresolved*"),
+            Some(ApplicationCommandInteractionDataOptionValue::String(s)) => {
+                DiscordEscaper { f, code: false }.write_str(s)
+            }
+            Some(ApplicationCommandInteractionDataOptionValue::Integer(i)) => write!(f, "{}", i),
+            Some(ApplicationCommandInteractionDataOptionValue::Boolean(b)) => write!(f, "{}", b),
+            Some(ApplicationCommandInteractionDataOptionValue::User(u, _)) => {
+                write!(f, "{}", u.mention())
+            }
+            Some(ApplicationCommandInteractionDataOptionValue::Channel(c)) => {
+                write!(f, "{}", c.id.mention())
+            }
+            Some(ApplicationCommandInteractionDataOptionValue::Role(r)) => {
+                write!(f, "{}", r.mention())
+            }
+            Some(_) => f.write_str(self.get_type_name()),
+        }
+    }
+}
+
+/// Longest embed field value Discord accepts, in characters
+const MAX_FIELD_LENGTH: usize = 1024;
+
+/// Cuts `value` down to `MAX_FIELD_LENGTH` characters, ending it with an ellipsis if it was longer
+fn truncate_field(mut value: String) -> String {
+    if let Some((end, _)) = value.char_indices().nth(MAX_FIELD_LENGTH) {
+        // make room for the ellipsis
+        let mut end = value[..end].char_indices().last().map_or(0, |(i, _)| i);
+        // don't leave half of an escape sequence to escape the ellipsis
+        let backslashes = value[..end]
+            .bytes()
+            .rev()
+            .take_while(|&b| b == b'\\')
+            .count();
+        if backslashes % 2 == 1 {
+            end -= 1;
+        }
+        value.truncate(end);
+        value.push('\u{2026}');
+    }
+    value
+}
+
+#[cfg(feature = "fxhash")]
+type MapHasher = fxhash_crate::FxBuildHasher;
+#[cfg(not(feature = "fxhash"))]
+type MapHasher = std::collections::hash_map::RandomState;
+
 /// Wrapper around `HashMap<String, SlashValue>`
-pub struct SlashMap(HashMap<String, SlashValue>);
+///
+/// With the `fxhash` feature, the map uses FxHash instead of the default SipHash
+#[derive(Debug)]
+pub struct SlashMap(HashMap<String, SlashValue, MapHasher>);
 
 impl SlashMap {
-    fn new() -> Self {
-        Self(HashMap::new())
+    fn with_capacity(capacity: usize) -> Self {
+        Self(HashMap::with_capacity_and_hasher(
+            capacity,
+            MapHasher::default(),
+        ))
+    }
+
+    /// Renders the arguments as `(name, value, inline)` embed fields, sorted by name
+    ///
+    /// Values longer than Discord's 1024 character field limit are cut short with an ellipsis.
+    /// Pass the result to `CreateEmbed::fields` for confirmation prompts or audit posts
+    pub fn to_fields(&self) -> Vec<(String, String, bool)> {
+        let mut fields: Vec<_> = self
+            .0
+            .iter()
+            .map(|(name, value)| (name.clone(), truncate_field(value.to_string()), true))
+            .collect();
+        fields.sort_by(|a, b| a.0.cmp(&b.0));
+        fields
+    }
+
+    /// Compares the map with a newer one, e.g. for "settings changed" audit messages
+    pub fn diff<'a>(&'a self, other: &'a SlashMap) -> SlashMapDiff<'a> {
+        diff::diff(self, other)
+    }
+
+    /// Returns a `Debug` view of the map with the values of `names` masked, for logging
+    pub fn redacted<'a>(&'a self, names: &'a [&'a str]) -> Redacted<'a> {
+        Redacted::new(self, names)
+    }
+
+    /// Returns the raw `SlashValue` for an argument, if it was supplied
+    pub fn get(&self, name: &str) -> Option<&SlashValue> {
+        self.0.get(name)
+    }
+
+    /// Overrides or injects the value of an argument, returning the previous one
+    ///
+    /// Useful for normalizing input (trimming strings, resolving shorthand) before handing the map
+    /// to a command's function
+    pub fn insert(
+        &mut self,
+        name: &str,
+        value: ApplicationCommandInteractionDataOptionValue,
+    ) -> Option<SlashValue> {
+        self.0
+            .insert(name.to_string(), SlashValue::from_value(name, value))
+    }
+
+    /// Removes an argument, returning its value if it was present
+    pub fn remove(&mut self, name: &str) -> Option<SlashValue> {
+        self.0.remove(name)
+    }
+
+    fn get_or_default<T>(
+        &self,
+        name: &str,
+        defaults: &impl DefaultsProvider,
+        getter: fn(&SlashValue) -> Result<T>,
+        default_getter: fn(&SlashValue) -> Result<T>,
+    ) -> Result<T> {
+        match self.0.get(name) {
+            Some(s) if s.is_present() => getter(s),
+            _ => match defaults.get_default(name) {
+                Some(value) => default_getter(&SlashValue::from_value(name, value)),
+                None => Err(Error::MissingValue {
+                    name: name.to_string(),
+                }),
+            },
+        }
     }
 
     /// If `SlashMap` has value, call `SlashValue::get_string()` on it
-    pub fn get_string<'a>(&'a self, name: &'a str) -> Result<'a, String> {
+    pub fn get_string(&self, name: &str) -> Result<String> {
         match self.0.get(name) {
             Some(s) => s.get_string(),
-            None => Err(Error::MissingValue { name }),
+            None => Err(Error::MissingValue {
+                name: name.to_string(),
+            }),
+        }
+    }
+
+    /// If `SlashMap` has value, call `SlashValue::get_string_matching()` on it
+    #[cfg(feature = "regex")]
+    pub fn get_string_matching(&self, name: &str, pattern: &regex::Regex) -> Result<String> {
+        match self.0.get(name) {
+            Some(s) => s.get_string_matching(pattern),
+            None => Err(Error::MissingValue {
+                name: name.to_string(),
+            }),
+        }
+    }
+
+    /// If `SlashMap` has value, call `SlashValue::get_string_with_length()` on it
+    pub fn get_string_with_length(
+        &self,
+        name: &str,
+        range: RangeInclusive<usize>,
+    ) -> Result<String> {
+        match self.0.get(name) {
+            Some(s) => s.get_string_with_length(range),
+            None => Err(Error::MissingValue {
+                name: name.to_string(),
+            }),
+        }
+    }
+
+    /// If `SlashMap` has value, call `SlashValue::get_string_one_of()` on it
+    pub fn get_string_one_of(
+        &self,
+        name: &str,
+        choices: &'static [&'static str],
+    ) -> Result<&'static str> {
+        match self.0.get(name) {
+            Some(s) => s.get_string_one_of(choices),
+            None => Err(Error::MissingValue {
+                name: name.to_string(),
+            }),
+        }
+    }
+
+    /// If `SlashMap` has value, call `SlashValue::get_bytes()` on it
+    pub fn get_bytes(&self, name: &str) -> Result<u64> {
+        match self.0.get(name) {
+            Some(s) => s.get_bytes(),
+            None => Err(Error::MissingValue {
+                name: name.to_string(),
+            }),
+        }
+    }
+
+    /// If `SlashMap` has value, call `SlashValue::get_percent()` on it
+    pub fn get_percent(&self, name: &str) -> Result<f64> {
+        match self.0.get(name) {
+            Some(s) => s.get_percent(),
+            None => Err(Error::MissingValue {
+                name: name.to_string(),
+            }),
+        }
+    }
+
+    /// If `SlashMap` has value, call `SlashValue::get_time_of_day()` on it
+    pub fn get_time_of_day(
+        &self,
+        name: &str,
+        tz: &impl TimezoneProvider,
+    ) -> Result<DateTime<FixedOffset>> {
+        match self.0.get(name) {
+            Some(s) => s.get_time_of_day(tz),
+            None => Err(Error::MissingValue {
+                name: name.to_string(),
+            }),
+        }
+    }
+
+    /// If `SlashMap` has value, call `SlashValue::get_cron()` on it
+    #[cfg(feature = "cron")]
+    pub fn get_cron(&self, name: &str) -> Result<cron::Schedule> {
+        match self.0.get(name) {
+            Some(s) => s.get_cron(),
+            None => Err(Error::MissingValue {
+                name: name.to_string(),
+            }),
+        }
+    }
+
+    /// If `SlashMap` has value, call `SlashValue::get_message_link()` on it
+    pub fn get_message_link(&self, name: &str) -> Result<MessageLink> {
+        match self.0.get(name) {
+            Some(s) => s.get_message_link(),
+            None => Err(Error::MissingValue {
+                name: name.to_string(),
+            }),
+        }
+    }
+
+    /// If `SlashMap` has value, call `SlashValue::get_invite()` on it
+    pub fn get_invite(&self, name: &str) -> Result<String> {
+        match self.0.get(name) {
+            Some(s) => s.get_invite(),
+            None => Err(Error::MissingValue {
+                name: name.to_string(),
+            }),
+        }
+    }
+
+    /// If `SlashMap` has value, call `SlashValue::get_semver()` on it
+    #[cfg(feature = "semver")]
+    pub fn get_semver(&self, name: &str) -> Result<semver::Version> {
+        match self.0.get(name) {
+            Some(s) => s.get_semver(),
+            None => Err(Error::MissingValue {
+                name: name.to_string(),
+            }),
+        }
+    }
+
+    /// If `SlashMap` has value, call `SlashValue::get_semver_req()` on it
+    #[cfg(feature = "semver")]
+    pub fn get_semver_req(&self, name: &str) -> Result<semver::VersionReq> {
+        match self.0.get(name) {
+            Some(s) => s.get_semver_req(),
+            None => Err(Error::MissingValue {
+                name: name.to_string(),
+            }),
+        }
+    }
+
+    /// If `SlashMap` has value, call `SlashValue::get_math_expr()` on it
+    pub fn get_math_expr(&self, name: &str) -> Result<f64> {
+        match self.0.get(name) {
+            Some(s) => s.get_math_expr(),
+            None => Err(Error::MissingValue {
+                name: name.to_string(),
+            }),
+        }
+    }
+
+    /// If `SlashMap` has value, call `SlashValue::get_math_expr_int()` on it
+    pub fn get_math_expr_int(&self, name: &str) -> Result<i64> {
+        match self.0.get(name) {
+            Some(s) => s.get_math_expr_int(),
+            None => Err(Error::MissingValue {
+                name: name.to_string(),
+            }),
+        }
+    }
+
+    /// If `SlashMap` has value, call `SlashValue::get_user_list()` on it
+    pub fn get_user_list(&self, name: &str) -> Result<Vec<UserId>> {
+        match self.0.get(name) {
+            Some(s) => s.get_user_list(),
+            None => Err(Error::MissingValue {
+                name: name.to_string(),
+            }),
+        }
+    }
+
+    /// If `SlashMap` has value, call `SlashValue::get_parsed()` on it
+    pub fn get_parsed<T>(&self, name: &str) -> Result<T>
+    where
+        T: FromStr,
+        T::Err: std::error::Error + Send + Sync + 'static,
+    {
+        match self.0.get(name) {
+            Some(s) => s.get_parsed(),
+            None => Err(Error::MissingValue {
+                name: name.to_string(),
+            }),
         }
     }
 
     /// If `SlashMap` has value, call `SlashValue::get_integer()` on it
-    pub fn get_integer<'a>(&'a self, name: &'a str) -> Result<'a, i64> {
+    pub fn get_integer(&self, name: &str) -> Result<i64> {
         match self.0.get(name) {
             Some(s) => s.get_integer(),
-            None => Err(Error::MissingValue { name }),
+            None => Err(Error::MissingValue {
+                name: name.to_string(),
+            }),
+        }
+    }
+
+    /// If `SlashMap` has value, call `SlashValue::get_integer_in()` on it
+    pub fn get_integer_in(&self, name: &str, range: RangeInclusive<i64>) -> Result<i64> {
+        match self.0.get(name) {
+            Some(s) => s.get_integer_in(range),
+            None => Err(Error::MissingValue {
+                name: name.to_string(),
+            }),
         }
     }
 
     /// If `SlashMap` has value, call `SlashValue::get_boolean()` on it
-    pub fn get_boolean<'a>(&'a self, name: &'a str) -> Result<'a, bool> {
+    pub fn get_boolean(&self, name: &str) -> Result<bool> {
         match self.0.get(name) {
             Some(s) => s.get_boolean(),
-            None => Err(Error::MissingValue { name }),
+            None => Err(Error::MissingValue {
+                name: name.to_string(),
+            }),
+        }
+    }
+
+    /// Like `get_boolean()`, but returns `None` if the value wasn't given instead of erroring
+    ///
+    /// This lets commands tell "explicitly false" apart from "not given"
+    pub fn get_boolean_opt(&self, name: &str) -> Result<Option<bool>> {
+        match self.0.get(name) {
+            Some(s) if s.is_present() => s.get_boolean().map(Some),
+            _ => Ok(None),
         }
     }
 
     /// If `SlashMap` has value, call `SlashValue::get_user()` on it
-    pub fn get_user<'a>(&'a self, name: &'a str) -> Result<'a, UserOrMember> {
+    pub fn get_user(&self, name: &str) -> Result<UserOrMember> {
         match self.0.get(name) {
             Some(s) => s.get_user(),
-            None => Err(Error::MissingValue { name }),
+            None => Err(Error::MissingValue {
+                name: name.to_string(),
+            }),
+        }
+    }
+
+    /// If `SlashMap` has value, call `SlashValue::get_human_user()` on it
+    pub fn get_human_user(&self, name: &str) -> Result<UserOrMember> {
+        match self.0.get(name) {
+            Some(s) => s.get_human_user(),
+            None => Err(Error::MissingValue {
+                name: name.to_string(),
+            }),
+        }
+    }
+
+    /// If `SlashMap` has value, call `SlashValue::get_other_user()` on it
+    pub fn get_other_user(&self, name: &str, invoker: UserId) -> Result<UserOrMember> {
+        match self.0.get(name) {
+            Some(s) => s.get_other_user(invoker),
+            None => Err(Error::MissingValue {
+                name: name.to_string(),
+            }),
         }
     }
 
+    /// Calls `SlashValue::get_user()` on each of `names` that has a value, skipping the rest
+    ///
+    /// Handy for commands with several optional user slots
+    pub fn get_users(&self, names: &[&str]) -> Result<Vec<UserOrMember>> {
+        let mut users = Vec::with_capacity(names.len());
+        for name in names {
+            match self.0.get(*name) {
+                Some(s) if s.is_present() => users.push(s.get_user()?),
+                _ => {}
+            }
+        }
+        Ok(users)
+    }
+
     /// If `SlashMap` has value, call `SlashValue::get_channel()` on it
-    pub fn get_channel<'a>(&'a self, name: &'a str) -> Result<'a, PartialChannel> {
+    pub fn get_channel(&self, name: &str) -> Result<PartialChannel> {
         match self.0.get(name) {
             Some(s) => s.get_channel(),
-            None => Err(Error::MissingValue { name }),
+            None => Err(Error::MissingValue {
+                name: name.to_string(),
+            }),
+        }
+    }
+
+    /// If `SlashMap` has value, call `SlashValue::get_guild_channel()` on it
+    pub fn get_guild_channel(
+        &self,
+        name: &str,
+        in_guild: impl Fn(ChannelId) -> bool,
+    ) -> Result<PartialChannel> {
+        match self.0.get(name) {
+            Some(s) => s.get_guild_channel(in_guild),
+            None => Err(Error::MissingValue {
+                name: name.to_string(),
+            }),
+        }
+    }
+
+    /// If `SlashMap` has value, call `SlashValue::get_channel_of_kind()` on it
+    pub fn get_channel_of_kind(&self, name: &str, kinds: &[ChannelType]) -> Result<PartialChannel> {
+        match self.0.get(name) {
+            Some(s) => s.get_channel_of_kind(kinds),
+            None => Err(Error::MissingValue {
+                name: name.to_string(),
+            }),
         }
     }
 
     /// If `SlashMap` has value, call `SlashValue::get_role()` on it
-    pub fn get_role<'a>(&'a self, name: &'a str) -> Result<'a, Role> {
+    pub fn get_role(&self, name: &str) -> Result<Role> {
         match self.0.get(name) {
             Some(s) => s.get_role(),
-            None => Err(Error::MissingValue { name }),
+            None => Err(Error::MissingValue {
+                name: name.to_string(),
+            }),
         }
     }
 
     /// If `SlashMap` has value, call `SlashValue::get_mentionable()` on it
-    pub fn get_mentionable<'a>(&'a self, name: &'a str) -> Result<'a, Mentionable> {
+    pub fn get_mentionable(&self, name: &str) -> Result<Mentionable> {
         match self.0.get(name) {
             Some(s) => s.get_mentionable(),
-            None => Err(Error::MissingValue { name }),
+            None => Err(Error::MissingValue {
+                name: name.to_string(),
+            }),
         }
     }
+
+    /// Like `get_string()`, but falls back to `defaults` if the value wasn't given
+    pub fn get_string_or_default(
+        &self,
+        name: &str,
+        defaults: &impl DefaultsProvider,
+    ) -> Result<String> {
+        self.get_or_default(
+            name,
+            defaults,
+            SlashValue::get_string,
+            SlashValue::get_string,
+        )
+    }
+
+    /// Like `get_integer()`, but falls back to `defaults` if the value wasn't given
+    pub fn get_integer_or_default(
+        &self,
+        name: &str,
+        defaults: &impl DefaultsProvider,
+    ) -> Result<i64> {
+        self.get_or_default(
+            name,
+            defaults,
+            SlashValue::get_integer,
+            SlashValue::get_integer_from_text,
+        )
+    }
+
+    /// Like `get_boolean()`, but falls back to `defaults` if the value wasn't given
+    pub fn get_boolean_or_default(
+        &self,
+        name: &str,
+        defaults: &impl DefaultsProvider,
+    ) -> Result<bool> {
+        self.get_or_default(
+            name,
+            defaults,
+            SlashValue::get_boolean,
+            SlashValue::get_boolean_from_text,
+        )
+    }
+
+    /// Like `get_user()`, but falls back to `defaults` if the value wasn't given
+    pub fn get_user_or_default(
+        &self,
+        name: &str,
+        defaults: &impl DefaultsProvider,
+    ) -> Result<UserOrMember> {
+        self.get_or_default(name, defaults, SlashValue::get_user, SlashValue::get_user)
+    }
+
+    /// Like `get_channel()`, but falls back to `defaults` if the value wasn't given
+    pub fn get_channel_or_default(
+        &self,
+        name: &str,
+        defaults: &impl DefaultsProvider,
+    ) -> Result<PartialChannel> {
+        self.get_or_default(
+            name,
+            defaults,
+            SlashValue::get_channel,
+            SlashValue::get_channel,
+        )
+    }
+
+    /// Like `get_role()`, but falls back to `defaults` if the value wasn't given
+    pub fn get_role_or_default(
+        &self,
+        name: &str,
+        defaults: &impl DefaultsProvider,
+    ) -> Result<Role> {
+        self.get_or_default(name, defaults, SlashValue::get_role, SlashValue::get_role)
+    }
+
+    /// Like `get_mentionable()`, but falls back to `defaults` if the value wasn't given
+    pub fn get_mentionable_or_default(
+        &self,
+        name: &str,
+        defaults: &impl DefaultsProvider,
+    ) -> Result<Mentionable> {
+        self.get_or_default(
+            name,
+            defaults,
+            SlashValue::get_mentionable,
+            SlashValue::get_mentionable,
+        )
+    }
 }
 
 /// For derive macros
 pub trait FromSlashMap {
-    fn from_slash_map<'a>(_: SlashMap) -> Result<'a, Self>
+    fn from_slash_map(_: SlashMap) -> Result<Self>
     where
         Self: Sized;
 }
 
-/// Processes a `ApplicationCommandInteractionData` and returns the path and arguments
-pub fn process(interaction: &ApplicationCommandInteractionData) -> (String, SlashMap) {
-    // traverse
-    let mut options = &interaction.options;
-    let mut path = vec![interaction.name.clone()];
-
-    loop {
-        match options.get(0) {
-            None => break,
-            Some(option) => {
-                if matches!(
-                    option.kind,
-                    ApplicationCommandOptionType::SubCommand
-                        | ApplicationCommandOptionType::SubCommandGroup
-                ) {
-                    path.push(option.name.clone());
-                    options = &option.options;
-                } else {
-                    break;
-                }
+/// Processes a `ApplicationCommandInteractionData` for the command at `expected_path` straight into
+/// `T`, erroring if a different command was invoked
+pub fn process_into<T: FromSlashMap>(
+    interaction: &ApplicationCommandInteractionData,
+    expected_path: &str,
+) -> Result<T> {
+    let (path, map) = process(interaction);
+    if path.as_str() != expected_path {
+        return Err(Error::PathMismatch {
+            expected: expected_path.to_string(),
+            found: path.to_string(),
+        });
+    }
+    T::from_slash_map(map)
+}
+
+/// Parses a JSON `ApplicationCommandInteractionData` and processes it like `process`
+///
+/// Meant for bots receiving interactions over HTTP. Neither this nor `process` panics, whatever
+/// the input
+pub fn process_json(data: &[u8]) -> Result<(CommandPath, SlashMap)> {
+    let interaction: ApplicationCommandInteractionData =
+        serde_json::from_slice(data).map_err(|e| Error::InvalidPayload(Box::new(e)))?;
+    let (path, mut map) = process(&interaction);
+    if map.0.values().any(|v| v.type_number.is_none()) {
+        recover_type_numbers(data, &mut map);
+    }
+    Ok((path, map))
+}
+
+/// Returns the number of an option type, or `None` for types serenity doesn't know
+fn type_number(kind: ApplicationCommandOptionType) -> Option<u8> {
+    match kind {
+        ApplicationCommandOptionType::Unknown => None,
+        kind => Some(kind as u8),
+    }
+}
+
+/// Fills in the type numbers serenity threw away for option types it doesn't know, by reading
+/// them from the raw payload
+fn recover_type_numbers(data: &[u8], map: &mut SlashMap) {
+    let data: serde_json::Value = match serde_json::from_slice(data) {
+        Ok(data) => data,
+        Err(_) => return,
+    };
+    // follow subcommands down to the arguments, like path_segments
+    let mut options = &data["options"];
+    while let Some(first) = options.get(0) {
+        match first["type"].as_u64() {
+            Some(1) | Some(2) => options = &first["options"],
+            _ => break,
+        }
+    }
+    for option in options.as_array().into_iter().flatten() {
+        let value = option["name"].as_str().and_then(|name| map.0.get_mut(name));
+        if let (Some(value), Some(kind)) = (value, option["type"].as_u64()) {
+            if value.type_number.is_none() {
+                value.type_number = u8::try_from(kind).ok();
             }
         }
     }
+}
+
+/// Like `process_json`, but rejects payloads exceeding `limits`
+///
+/// Size, nesting and option count are checked before deserializing, so oversized payloads are
+/// cheap to turn away on public HTTP endpoints
+pub fn process_json_limited(
+    data: &[u8],
+    limits: &PayloadLimits,
+) -> Result<(CommandPath, SlashMap)> {
+    if data.len() > limits.max_bytes {
+        return Err(Error::PayloadLimitExceeded { limit: "size" });
+    }
+    if limits::json_depth(data) > limits.max_depth {
+        return Err(Error::PayloadLimitExceeded { limit: "depth" });
+    }
+    if limits::exceeds_max_options(data, limits.max_options)
+        .map_err(|e| Error::InvalidPayload(Box::new(e)))?
+    {
+        return Err(Error::PayloadLimitExceeded { limit: "options" });
+    }
+    process_json(data)
+}
+
+/// Like `process_json`, but parses with simd-json, which uses `data` as scratch space
+///
+/// simd-json only uses SIMD when the target enables it, e.g. with
+/// `RUSTFLAGS="-C target-cpu=native"`, and falls back to portable code otherwise. Because
+/// `data` is overwritten, the numbers of option types serenity doesn't know can't be recovered,
+/// so `Error::UnsupportedType` has no `kind`
+#[cfg(feature = "simd-json")]
+pub fn process_json_mut(data: &mut [u8]) -> Result<(CommandPath, SlashMap)> {
+    let interaction: ApplicationCommandInteractionData =
+        simd_json::serde::from_slice(data).map_err(|e| Error::InvalidPayload(Box::new(e)))?;
+    Ok(process(&interaction))
+}
+
+/// Processes a `ApplicationCommandInteractionData` and returns the path and arguments
+pub fn process(interaction: &ApplicationCommandInteractionData) -> (CommandPath, SlashMap) {
+    // traverse
+    let mut segments = path_segments(interaction);
+    let path = CommandPath::new(segments.by_ref());
+    let options = segments.options;
 
     // map data
-    let mut map = SlashMap::new();
+    let mut map = SlashMap::with_capacity(options.len());
     for option in options {
         map.0.insert(
             option.name.clone(),
             SlashValue {
                 inner: option.resolved.clone(),
                 name: option.name.clone(),
+                kind: option.kind,
+                type_number: type_number(option.kind),
+                raw: match option.resolved {
+                    Some(_) => None,
+                    None => option.value.clone(),
+                },
             },
         );
     }
 
-    (path.join(" "), map)
+    (path, map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unknown_value(name: &str) -> SlashValue {
+        SlashValue {
+            inner: None,
+            name: name.to_string(),
+            kind: ApplicationCommandOptionType::Unknown,
+            type_number: None,
+            raw: Some(serde_json::Value::from(1.5)),
+        }
+    }
+
+    #[test]
+    fn recovers_unknown_type_numbers() {
+        let mut map = SlashMap::with_capacity(2);
+        map.0.insert("amount".to_string(), unknown_value("amount"));
+        map.0.insert("other".to_string(), unknown_value("other"));
+        let data = br#"{"name":"cmd","options":[{"name":"sub","type":1,"options":[
+            {"name":"amount","type":10,"value":1.5},
+            {"name":"other","type":300,"value":1}
+        ]}]}"#;
+        recover_type_numbers(data, &mut map);
+        assert_eq!(map.0["amount"].type_number, Some(10));
+        assert_eq!(map.0["other"].type_number, None);
+        let e = map.0["amount"].expect_some().unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "Field `amount` has an unsupported option type (10)"
+        );
+        let e = map.0["other"].expect_some().unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "Field `other` has an unsupported option type"
+        );
+    }
+
+    #[test]
+    fn truncate_field_limits_characters() {
+        assert_eq!(truncate_field("short".to_string()), "short");
+        let exact = "é".repeat(MAX_FIELD_LENGTH);
+        assert_eq!(truncate_field(exact.clone()), exact);
+        let long = truncate_field("é".repeat(MAX_FIELD_LENGTH + 1));
+        assert_eq!(long.chars().count(), MAX_FIELD_LENGTH);
+        assert!(long.ends_with('\u{2026}'));
+    }
+
+    #[test]
+    fn truncate_field_keeps_escapes_whole() {
+        let value = format!("{}\\*\\*", "a".repeat(MAX_FIELD_LENGTH - 2));
+        let truncated = truncate_field(value);
+        assert!(truncated.ends_with("a\u{2026}"));
+    }
 }
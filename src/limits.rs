@@ -0,0 +1,249 @@
+use serde::de::{DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use std::fmt::Formatter;
+
+/// Limits `process_json_limited` enforces on untrusted interaction payloads
+#[derive(Debug, Clone, Copy)]
+pub struct PayloadLimits {
+    /// Largest accepted payload, in bytes
+    pub max_bytes: usize,
+    /// Deepest accepted nesting of JSON objects and arrays
+    pub max_depth: usize,
+    /// Most options accepted across the whole payload, counting every entry of every `options`
+    /// array (subcommands included) and every entry of the `resolved` maps
+    pub max_options: usize,
+}
+
+impl Default for PayloadLimits {
+    fn default() -> Self {
+        Self {
+            max_bytes: 256 * 1024,
+            max_depth: 32,
+            // 25 arguments, each resolving to a user and a member, plus a subcommand group
+            max_options: 80,
+        }
+    }
+}
+
+/// Returns the deepest nesting of objects and arrays in `data`, without parsing it
+pub(crate) fn json_depth(data: &[u8]) -> usize {
+    let mut depth = 0usize;
+    let mut max = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for &b in data {
+        if in_string {
+            match b {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match b {
+            b'"' => in_string = true,
+            b'{' | b'[' => {
+                depth += 1;
+                max = max.max(depth);
+            }
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    max
+}
+
+/// Counts options across a payload by streaming over it, erroring as soon as there are more than
+/// `max`, so nothing is allocated for the options themselves
+pub(crate) fn exceeds_max_options(data: &[u8], max: usize) -> serde_json::Result<bool> {
+    let mut counter = OptionCounter {
+        count: 0,
+        max,
+        exceeded: false,
+    };
+    let mut de = serde_json::Deserializer::from_slice(data);
+    match Node(&mut counter).deserialize(&mut de) {
+        Err(_) if counter.exceeded => Ok(true),
+        result => result.and_then(|_| de.end()).map(|_| false),
+    }
+}
+
+struct OptionCounter {
+    count: usize,
+    max: usize,
+    exceeded: bool,
+}
+
+impl OptionCounter {
+    fn bump<E: serde::de::Error>(&mut self) -> Result<(), E> {
+        self.count += 1;
+        if self.count > self.max {
+            self.exceeded = true;
+            Err(E::custom("too many options"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// The interaction data or one of its options, which may nest further `options`
+struct Node<'a>(&'a mut OptionCounter);
+
+impl<'de> DeserializeSeed<'de> for Node<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Node<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("an object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "options" => map.next_value_seed(OptionList(&mut *self.0))?,
+                "resolved" => map.next_value_seed(Resolved(&mut *self.0))?,
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An `options` array
+struct OptionList<'a>(&'a mut OptionCounter);
+
+impl<'de> DeserializeSeed<'de> for OptionList<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for OptionList<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("an array of options")
+    }
+
+    fn visit_unit<E>(self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while seq.next_element_seed(Node(&mut *self.0))?.is_some() {
+            self.0.bump()?;
+        }
+        Ok(())
+    }
+}
+
+/// The `resolved` object, mapping each kind (`users`, `members`, ...) to a map of entries by ID
+struct Resolved<'a>(&'a mut OptionCounter);
+
+impl<'de> DeserializeSeed<'de> for Resolved<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Resolved<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("an object of resolved entries")
+    }
+
+    fn visit_unit<E>(self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        while map.next_key::<IgnoredAny>()?.is_some() {
+            map.next_value_seed(ResolvedEntries(&mut *self.0))?;
+        }
+        Ok(())
+    }
+}
+
+/// One map of resolved entries, keyed by ID
+struct ResolvedEntries<'a>(&'a mut OptionCounter);
+
+impl<'de> DeserializeSeed<'de> for ResolvedEntries<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ResolvedEntries<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("an object of resolved entries")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        while map.next_key::<IgnoredAny>()?.is_some() {
+            self.0.bump()?;
+            map.next_value::<IgnoredAny>()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_options_across_the_tree() {
+        let leaf = r#"{"name":"a","type":3,"value":"x"}"#;
+        let siblings = vec![leaf; 30].join(",");
+        let nested = format!(
+            r#"{{"name":"cmd","options":[{{"name":"sub","type":1,"options":[{}]}},{}]}}"#,
+            leaf, siblings
+        );
+        assert!(!exceeds_max_options(nested.as_bytes(), 40).unwrap());
+        assert!(exceeds_max_options(nested.as_bytes(), 25).unwrap());
+    }
+
+    #[test]
+    fn counts_resolved_entries() {
+        let users: Vec<_> = (0..30).map(|i| format!(r#""{}":{{}}"#, i)).collect();
+        let data = format!(
+            r#"{{"name":"cmd","resolved":{{"users":{{{}}},"roles":{{"1":{{}}}}}}}}"#,
+            users.join(",")
+        );
+        assert!(!exceeds_max_options(data.as_bytes(), 31).unwrap());
+        assert!(exceeds_max_options(data.as_bytes(), 30).unwrap());
+    }
+
+    #[test]
+    fn rejects_invalid_json() {
+        assert!(exceeds_max_options(br#"{"options":[}"#, 25).is_err());
+        assert!(exceeds_max_options(br#"{"name":"a"} x"#, 25).is_err());
+        assert!(exceeds_max_options(br#"[]"#, 25).is_err());
+        assert!(exceeds_max_options(br#"{"options":"x"}"#, 25).is_err());
+        assert!(!exceeds_max_options(br#"{"options":null,"resolved":null}"#, 25).unwrap());
+    }
+
+    #[test]
+    fn depth_ignores_brackets_in_strings() {
+        assert_eq!(json_depth(br#"{"a":[[1]]}"#), 3);
+        assert_eq!(json_depth(br#"{"a":"[[[\"{{"}"#), 1);
+    }
+}
@@ -0,0 +1,376 @@
+use crate::UserOrMember;
+use serenity::model::guild::Role;
+use serenity::model::id::{RoleId, UserId};
+use serenity::model::permissions::Permissions;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+/// Moderation actions that `can_moderate` can check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModerationAction {
+    Ban,
+    Kick,
+}
+
+impl ModerationAction {
+    fn required_permission(self) -> Permissions {
+        match self {
+            ModerationAction::Ban => Permissions::BAN_MEMBERS,
+            ModerationAction::Kick => Permissions::KICK_MEMBERS,
+        }
+    }
+}
+
+/// Why a moderation action can't be performed on a target
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModerationRefusal {
+    /// The invoker targeted themselves
+    SelfTarget,
+    /// The invoker targeted the bot
+    BotTarget,
+    /// The target owns the guild
+    TargetIsOwner,
+    /// The target isn't in the guild, so the action doesn't apply
+    NotInGuild,
+    /// The target's highest role is at or above the invoker's
+    InvokerTooLow,
+    /// The target's highest role is at or above the bot's
+    BotTooLow,
+    /// The bot doesn't have the permission the action needs
+    BotMissingPermission(Permissions),
+}
+
+impl Display for ModerationRefusal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModerationRefusal::SelfTarget => f.write_str("You can't target yourself"),
+            ModerationRefusal::BotTarget => f.write_str("You can't target the bot"),
+            ModerationRefusal::TargetIsOwner => f.write_str("You can't target the server owner"),
+            ModerationRefusal::NotInGuild => f.write_str("That user isn't in this server"),
+            ModerationRefusal::InvokerTooLow => {
+                f.write_str("That user's highest role is not below yours")
+            }
+            ModerationRefusal::BotTooLow => {
+                f.write_str("That user's highest role is not below the bot's")
+            }
+            ModerationRefusal::BotMissingPermission(p) => {
+                write!(f, "The bot is missing the `{:?}` permission", p)
+            }
+        }
+    }
+}
+
+/// The guild state `can_moderate` checks against
+pub struct ModerationContext<'a> {
+    /// The owner of the guild
+    pub owner_id: UserId,
+    /// All roles of the guild, including `@everyone`
+    pub roles: &'a HashMap<RoleId, Role>,
+    /// The bot's own user ID
+    pub bot_id: UserId,
+    /// The roles the bot has in the guild
+    pub bot_roles: &'a [RoleId],
+}
+
+impl ModerationContext<'_> {
+    fn highest_position(&self, roles: &[RoleId]) -> i64 {
+        roles
+            .iter()
+            .filter_map(|id| self.roles.get(id))
+            .map(|role| role.position)
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn bot_permissions(&self) -> Permissions {
+        let mut permissions = Permissions::empty();
+        for role in self.roles.values() {
+            // the @everyone role shares its ID with the guild
+            if role.id.0 == role.guild_id.0 || self.bot_roles.contains(&role.id) {
+                permissions |= role.permissions;
+            }
+        }
+        permissions
+    }
+}
+
+/// Checks self/bot targeting, role hierarchy, and bot permissions for a moderation action
+pub fn can_moderate(
+    ctx: &ModerationContext,
+    invoker: UserId,
+    invoker_roles: &[RoleId],
+    target: &UserOrMember,
+    action: ModerationAction,
+) -> Result<(), ModerationRefusal> {
+    let target_id = target.get_user().id;
+    if target_id == invoker {
+        return Err(ModerationRefusal::SelfTarget);
+    }
+    if target_id == ctx.bot_id {
+        return Err(ModerationRefusal::BotTarget);
+    }
+    if target_id == ctx.owner_id {
+        return Err(ModerationRefusal::TargetIsOwner);
+    }
+
+    let bot_permissions = ctx.bot_permissions();
+    let required = action.required_permission();
+    if !bot_permissions.contains(Permissions::ADMINISTRATOR) && !bot_permissions.contains(required)
+    {
+        return Err(ModerationRefusal::BotMissingPermission(required));
+    }
+
+    let target_position = match target.get_member() {
+        Some(member) => ctx.highest_position(&member.roles),
+        None if action == ModerationAction::Kick => return Err(ModerationRefusal::NotInGuild),
+        // users outside the guild have no roles to compare against
+        None => return Ok(()),
+    };
+    if invoker != ctx.owner_id && target_position >= ctx.highest_position(invoker_roles) {
+        return Err(ModerationRefusal::InvokerTooLow);
+    }
+    if target_position >= ctx.highest_position(ctx.bot_roles) {
+        return Err(ModerationRefusal::BotTooLow);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use serenity::model::guild::PartialMember;
+    use serenity::model::user::User;
+
+    // the @everyone role shares its ID with the guild
+    const EVERYONE: u64 = 1;
+    const MEMBER: u64 = 10;
+    const MODERATOR: u64 = 11;
+    const ADMIN: u64 = 12;
+    const OWNER: UserId = UserId(100);
+    const BOT: UserId = UserId(101);
+    const INVOKER: UserId = UserId(102);
+    const TARGET: UserId = UserId(103);
+
+    fn role(id: u64, position: i64, permissions: Permissions) -> (RoleId, Role) {
+        let role = serde_json::from_value(json!({
+            "id": id.to_string(),
+            "guild_id": EVERYONE.to_string(),
+            "color": 0,
+            "hoist": false,
+            "managed": false,
+            "mentionable": false,
+            "name": "role",
+            "permissions": permissions.bits().to_string(),
+            "position": position,
+        }))
+        .unwrap();
+        (RoleId(id), role)
+    }
+
+    /// `@everyone` with `everyone` permissions, and member < moderator < admin
+    fn roles(everyone: Permissions) -> HashMap<RoleId, Role> {
+        vec![
+            role(EVERYONE, 0, everyone),
+            role(MEMBER, 1, Permissions::empty()),
+            role(
+                MODERATOR,
+                5,
+                Permissions::KICK_MEMBERS | Permissions::BAN_MEMBERS,
+            ),
+            role(ADMIN, 9, Permissions::ADMINISTRATOR),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    fn user(id: UserId) -> User {
+        serde_json::from_value(json!({
+            "id": id.0.to_string(),
+            "username": "user",
+            "discriminator": "0001",
+            "avatar": null,
+            "bot": false,
+        }))
+        .unwrap()
+    }
+
+    fn member(id: UserId, roles: &[u64]) -> UserOrMember {
+        let roles: Vec<_> = roles.iter().map(u64::to_string).collect();
+        let member = serde_json::from_value::<PartialMember>(json!({
+            "roles": roles,
+            "deaf": false,
+            "mute": false,
+            "joined_at": null,
+        }))
+        .unwrap();
+        UserOrMember::Member(user(id), member)
+    }
+
+    fn check(
+        roles: &HashMap<RoleId, Role>,
+        bot_roles: &[RoleId],
+        invoker: UserId,
+        invoker_roles: &[RoleId],
+        target: &UserOrMember,
+        action: ModerationAction,
+    ) -> Result<(), ModerationRefusal> {
+        let ctx = ModerationContext {
+            owner_id: OWNER,
+            roles,
+            bot_id: BOT,
+            bot_roles,
+        };
+        can_moderate(&ctx, invoker, invoker_roles, target, action)
+    }
+
+    #[test]
+    fn refuses_self_bot_and_owner_targets() {
+        let roles = roles(Permissions::empty());
+        let admin = [RoleId(ADMIN)];
+        for (target, refusal) in [
+            (INVOKER, ModerationRefusal::SelfTarget),
+            (BOT, ModerationRefusal::BotTarget),
+            (OWNER, ModerationRefusal::TargetIsOwner),
+        ] {
+            let target = member(target, &[]);
+            let result = check(
+                &roles,
+                &admin,
+                INVOKER,
+                &admin,
+                &target,
+                ModerationAction::Ban,
+            );
+            assert_eq!(result, Err(refusal));
+        }
+    }
+
+    #[test]
+    fn refuses_when_bot_lacks_permission() {
+        let roles = roles(Permissions::empty());
+        let target = member(TARGET, &[]);
+        let result = check(
+            &roles,
+            &[RoleId(MEMBER)],
+            INVOKER,
+            &[RoleId(ADMIN)],
+            &target,
+            ModerationAction::Ban,
+        );
+        assert_eq!(
+            result,
+            Err(ModerationRefusal::BotMissingPermission(
+                Permissions::BAN_MEMBERS
+            ))
+        );
+    }
+
+    #[test]
+    fn merges_everyone_permissions() {
+        let roles = roles(Permissions::KICK_MEMBERS);
+        let target = member(TARGET, &[]);
+        let kick = check(
+            &roles,
+            &[RoleId(MEMBER)],
+            INVOKER,
+            &[RoleId(MODERATOR)],
+            &target,
+            ModerationAction::Kick,
+        );
+        assert_eq!(kick, Ok(()));
+        let ban = check(
+            &roles,
+            &[RoleId(MEMBER)],
+            INVOKER,
+            &[RoleId(MODERATOR)],
+            &target,
+            ModerationAction::Ban,
+        );
+        assert_eq!(
+            ban,
+            Err(ModerationRefusal::BotMissingPermission(
+                Permissions::BAN_MEMBERS
+            ))
+        );
+    }
+
+    #[test]
+    fn administrator_overrides_missing_permission() {
+        let roles = roles(Permissions::empty());
+        let target = member(TARGET, &[MODERATOR]);
+        let result = check(
+            &roles,
+            &[RoleId(ADMIN)],
+            OWNER,
+            &[],
+            &target,
+            ModerationAction::Ban,
+        );
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn equal_positions_are_refused() {
+        let roles = roles(Permissions::empty());
+        let target = member(TARGET, &[MODERATOR]);
+        let invoker = check(
+            &roles,
+            &[RoleId(ADMIN)],
+            INVOKER,
+            &[RoleId(MODERATOR)],
+            &target,
+            ModerationAction::Kick,
+        );
+        assert_eq!(invoker, Err(ModerationRefusal::InvokerTooLow));
+        let bot = check(
+            &roles,
+            &[RoleId(MODERATOR)],
+            INVOKER,
+            &[RoleId(ADMIN)],
+            &target,
+            ModerationAction::Kick,
+        );
+        assert_eq!(bot, Err(ModerationRefusal::BotTooLow));
+    }
+
+    #[test]
+    fn owner_bypasses_invoker_hierarchy() {
+        let roles = roles(Permissions::empty());
+        let target = member(TARGET, &[MODERATOR]);
+        let result = check(
+            &roles,
+            &[RoleId(ADMIN)],
+            OWNER,
+            &[],
+            &target,
+            ModerationAction::Kick,
+        );
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn non_members_can_be_banned_but_not_kicked() {
+        let roles = roles(Permissions::empty());
+        let target = UserOrMember::User(user(TARGET));
+        let kick = check(
+            &roles,
+            &[RoleId(MODERATOR)],
+            INVOKER,
+            &[RoleId(MEMBER)],
+            &target,
+            ModerationAction::Kick,
+        );
+        assert_eq!(kick, Err(ModerationRefusal::NotInGuild));
+        let ban = check(
+            &roles,
+            &[RoleId(MODERATOR)],
+            INVOKER,
+            &[RoleId(MEMBER)],
+            &target,
+            ModerationAction::Ban,
+        );
+        assert_eq!(ban, Ok(()));
+    }
+}
@@ -0,0 +1,654 @@
+//! Parsers for string options with structured content
+
+use chrono::NaiveTime;
+use serenity::model::id::{ChannelId, GuildId, MessageId, UserId};
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+
+/// Why a string option couldn't be parsed, used as the source of `Error::ParseFailed`
+#[derive(Debug)]
+pub struct ParseError(&'static str);
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub(crate) type ParseResult<T> = std::result::Result<T, ParseError>;
+
+/// Parses a byte size like `512`, `10MB`, `1.5 GiB`; decimal units are powers of 1000 and binary
+/// units powers of 1024
+pub(crate) fn parse_bytes(s: &str) -> ParseResult<u64> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" => 1_000,
+        "m" | "mb" => 1_000_000,
+        "g" | "gb" => 1_000_000_000,
+        "t" | "tb" => 1_000_000_000_000,
+        "kib" => 1 << 10,
+        "mib" => 1 << 20,
+        "gib" => 1 << 30,
+        "tib" => 1 << 40,
+        _ => return Err(ParseError("unknown unit, expected e.g. `10MB` or `512KiB`")),
+    };
+    let number: f64 = number
+        .parse()
+        .map_err(|_| ParseError("expected a size like `10MB` or `512KiB`"))?;
+    let bytes = (number * multiplier as f64).round();
+    // u64::MAX as f64 rounds up to 2^64, which doesn't fit
+    if bytes >= u64::MAX as f64 {
+        return Err(ParseError("size is too large"));
+    }
+    Ok(bytes as u64)
+}
+
+/// Converts a percentage between 0 and 100 into a fraction
+pub(crate) fn percent_to_fraction(percent: f64) -> ParseResult<f64> {
+    if (0.0..=100.0).contains(&percent) {
+        Ok(percent / 100.0)
+    } else {
+        Err(ParseError("percentage must be between 0% and 100%"))
+    }
+}
+
+/// Parses a percentage like `35%` or `35`, or a fraction like `0.35`, into a fraction
+///
+/// A bare whole number is a percentage, matching `Integer` options, while a bare number with a
+/// decimal point is a fraction, so `35%`, `35` and `0.35` are all `0.35`
+pub(crate) fn parse_percent(s: &str) -> ParseResult<f64> {
+    const EXPECTED: &str = "expected a percentage like `35%` or a fraction like `0.35`";
+    let s = s.trim();
+    if let Some(number) = s.strip_suffix('%') {
+        let percent = number
+            .trim_end()
+            .parse::<f64>()
+            .map_err(|_| ParseError(EXPECTED))?;
+        return percent_to_fraction(percent);
+    }
+    if !s.contains('.') {
+        let percent = s.parse::<u64>().map_err(|_| ParseError(EXPECTED))?;
+        return percent_to_fraction(percent as f64);
+    }
+    let fraction = s.parse::<f64>().map_err(|_| ParseError(EXPECTED))?;
+    if (0.0..=1.0).contains(&fraction) {
+        Ok(fraction)
+    } else {
+        Err(ParseError(
+            "a fraction must be between 0 and 1, add `%` for a percentage",
+        ))
+    }
+}
+
+/// Parses a time of day like `18:30`, `6pm` or `6:30 am`
+pub(crate) fn parse_time_of_day(s: &str) -> ParseResult<NaiveTime> {
+    const EXPECTED: &str = "expected a time like `18:30` or `6pm`";
+    let s = s.trim().to_ascii_lowercase();
+    let (clock, meridiem) = if let Some(clock) = s.strip_suffix("am") {
+        (clock.trim_end(), Some(false))
+    } else if let Some(clock) = s.strip_suffix("pm") {
+        (clock.trim_end(), Some(true))
+    } else {
+        (s.as_str(), None)
+    };
+    let (hour, minute) = match clock.split_once(':') {
+        Some((hour, minute)) => (hour, minute),
+        None => (clock, "0"),
+    };
+    let mut hour: u32 = hour.parse().map_err(|_| ParseError(EXPECTED))?;
+    let minute: u32 = minute.parse().map_err(|_| ParseError(EXPECTED))?;
+    if let Some(pm) = meridiem {
+        if !(1..=12).contains(&hour) {
+            return Err(ParseError("hour must be between 1 and 12 with am/pm"));
+        }
+        hour = hour % 12 + if pm { 12 } else { 0 };
+    }
+    NaiveTime::from_hms_opt(hour, minute, 0).ok_or(ParseError("that time doesn't exist"))
+}
+
+/// The parts of a Discord message link
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageLink {
+    /// The guild the message is in, or `None` for DMs
+    pub guild_id: Option<GuildId>,
+    pub channel_id: ChannelId,
+    pub message_id: MessageId,
+}
+
+/// Parses a message link like `https://discord.com/channels/1/2/3`
+pub(crate) fn parse_message_link(s: &str) -> ParseResult<MessageLink> {
+    const EXPECTED: &str = "expected a message link";
+    let s = s.trim().trim_start_matches('<').trim_end_matches('>');
+    let path = s
+        .strip_prefix("https://")
+        .or_else(|| s.strip_prefix("http://"))
+        .ok_or(ParseError(EXPECTED))?;
+    let path = path
+        .strip_prefix("ptb.")
+        .or_else(|| path.strip_prefix("canary."))
+        .unwrap_or(path);
+    let path = path
+        .strip_prefix("discord.com/channels/")
+        .or_else(|| path.strip_prefix("discordapp.com/channels/"))
+        .ok_or(ParseError(EXPECTED))?;
+    let mut parts = path.trim_end_matches('/').split('/');
+    let (guild, channel, message) = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(guild), Some(channel), Some(message), None) => (guild, channel, message),
+        _ => return Err(ParseError(EXPECTED)),
+    };
+    let id = |s: &str| s.parse::<u64>().map_err(|_| ParseError(EXPECTED));
+    Ok(MessageLink {
+        guild_id: match guild {
+            "@me" => None,
+            guild => Some(GuildId(id(guild)?)),
+        },
+        channel_id: ChannelId(id(channel)?),
+        message_id: MessageId(id(message)?),
+    })
+}
+
+/// Parses an invite code, or an invite link like `https://discord.gg/code`, into the code
+pub(crate) fn parse_invite(s: &str) -> ParseResult<String> {
+    let s = s.trim().trim_start_matches('<').trim_end_matches('>');
+    let s = s
+        .strip_prefix("https://")
+        .or_else(|| s.strip_prefix("http://"))
+        .unwrap_or(s);
+    let code = [
+        "discord.gg/",
+        "discord.com/invite/",
+        "discordapp.com/invite/",
+    ]
+    .iter()
+    .find_map(|prefix| s.strip_prefix(prefix))
+    .unwrap_or(s)
+    .trim_end_matches('/');
+    if !code.is_empty() && code.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        Ok(code.to_string())
+    } else {
+        Err(ParseError("expected an invite code or link"))
+    }
+}
+
+/// Deepest nesting of parentheses and unary operators `parse_math_expr` accepts
+const MAX_EXPR_DEPTH: usize = 32;
+
+const TOO_LARGE: ParseError = ParseError("result is too large");
+const NOT_WHOLE: ParseError = ParseError("result must be a whole number");
+
+/// Arithmetic for the values `ExprParser` evaluates to
+trait ExprNumber: Copy {
+    /// Builds a number from its digits (commas removed) times `10^scale`
+    fn literal(digits: &str, scale: u32) -> ParseResult<Self>;
+    fn add(self, rhs: Self) -> ParseResult<Self>;
+    fn sub(self, rhs: Self) -> ParseResult<Self>;
+    fn mul(self, rhs: Self) -> ParseResult<Self>;
+    fn div(self, rhs: Self) -> ParseResult<Self>;
+    fn neg(self) -> ParseResult<Self>;
+    fn pow(self, exponent: Self) -> ParseResult<Self>;
+}
+
+impl ExprNumber for f64 {
+    fn literal(digits: &str, scale: u32) -> ParseResult<Self> {
+        let number: f64 = digits.parse().map_err(|_| ParseError("invalid number"))?;
+        Ok(number * 10f64.powi(scale as i32))
+    }
+
+    fn add(self, rhs: Self) -> ParseResult<Self> {
+        Ok(self + rhs)
+    }
+
+    fn sub(self, rhs: Self) -> ParseResult<Self> {
+        Ok(self - rhs)
+    }
+
+    fn mul(self, rhs: Self) -> ParseResult<Self> {
+        Ok(self * rhs)
+    }
+
+    fn div(self, rhs: Self) -> ParseResult<Self> {
+        if rhs == 0.0 {
+            return Err(ParseError("can't divide by zero"));
+        }
+        Ok(self / rhs)
+    }
+
+    fn neg(self) -> ParseResult<Self> {
+        Ok(-self)
+    }
+
+    fn pow(self, exponent: Self) -> ParseResult<Self> {
+        Ok(self.powf(exponent))
+    }
+}
+
+// integers are evaluated exactly in i128, so i64::MIN can be typed as `-9223372036854775808`
+impl ExprNumber for i128 {
+    fn literal(digits: &str, scale: u32) -> ParseResult<Self> {
+        let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        if fraction.contains('.') || (whole.is_empty() && fraction.is_empty()) {
+            return Err(ParseError("invalid number"));
+        }
+        let scale = scale as usize;
+        let (kept, dropped) = fraction.split_at(fraction.len().min(scale));
+        if dropped.bytes().any(|c| c != b'0') {
+            return Err(NOT_WHOLE);
+        }
+        let mut number = String::with_capacity(whole.len() + scale);
+        number.push_str(whole);
+        number.push_str(kept);
+        number.push_str(&"0".repeat(scale - kept.len()));
+        number.parse().map_err(|_| TOO_LARGE)
+    }
+
+    fn add(self, rhs: Self) -> ParseResult<Self> {
+        self.checked_add(rhs).ok_or(TOO_LARGE)
+    }
+
+    fn sub(self, rhs: Self) -> ParseResult<Self> {
+        self.checked_sub(rhs).ok_or(TOO_LARGE)
+    }
+
+    fn mul(self, rhs: Self) -> ParseResult<Self> {
+        self.checked_mul(rhs).ok_or(TOO_LARGE)
+    }
+
+    fn div(self, rhs: Self) -> ParseResult<Self> {
+        if rhs == 0 {
+            return Err(ParseError("can't divide by zero"));
+        }
+        // i128::MIN / -1 overflows the remainder as well as the quotient
+        if self.checked_rem(rhs).ok_or(TOO_LARGE)? != 0 {
+            return Err(NOT_WHOLE);
+        }
+        self.checked_div(rhs).ok_or(TOO_LARGE)
+    }
+
+    fn neg(self) -> ParseResult<Self> {
+        self.checked_neg().ok_or(TOO_LARGE)
+    }
+
+    fn pow(self, exponent: Self) -> ParseResult<Self> {
+        if exponent < 0 {
+            return Err(NOT_WHOLE);
+        }
+        let exponent = u32::try_from(exponent).map_err(|_| TOO_LARGE)?;
+        self.checked_pow(exponent).ok_or(TOO_LARGE)
+    }
+}
+
+/// Recursive descent evaluator for `parse_math_expr`
+struct ExprParser<'a> {
+    input: &'a [u8],
+    pos: usize,
+    depth: usize,
+}
+
+impl ExprParser<'_> {
+    fn peek(&mut self) -> Option<u8> {
+        while self.input.get(self.pos) == Some(&b' ') {
+            self.pos += 1;
+        }
+        self.input.get(self.pos).copied()
+    }
+
+    fn descend(&mut self) -> ParseResult<()> {
+        self.depth += 1;
+        if self.depth > MAX_EXPR_DEPTH {
+            Err(ParseError("expression is nested too deeply"))
+        } else {
+            Ok(())
+        }
+    }
+
+    // expr := term (('+' | '-') term)*
+    fn expr<N: ExprNumber>(&mut self) -> ParseResult<N> {
+        let mut value: N = self.term()?;
+        loop {
+            match self.peek() {
+                Some(b'+') => {
+                    self.pos += 1;
+                    value = value.add(self.term()?)?;
+                }
+                Some(b'-') => {
+                    self.pos += 1;
+                    value = value.sub(self.term()?)?;
+                }
+                _ => return Ok(value),
+            }
+        }
+    }
+
+    // term := unary (('*' | '/') unary)*
+    fn term<N: ExprNumber>(&mut self) -> ParseResult<N> {
+        let mut value: N = self.unary()?;
+        loop {
+            match self.peek() {
+                Some(b'*') => {
+                    self.pos += 1;
+                    value = value.mul(self.unary()?)?;
+                }
+                Some(b'/') => {
+                    self.pos += 1;
+                    value = value.div(self.unary()?)?;
+                }
+                _ => return Ok(value),
+            }
+        }
+    }
+
+    // unary := '-' unary | power
+    fn unary<N: ExprNumber>(&mut self) -> ParseResult<N> {
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+            self.descend()?;
+            let value = self.unary::<N>()?.neg()?;
+            self.depth -= 1;
+            Ok(value)
+        } else {
+            self.power()
+        }
+    }
+
+    // power := atom ('^' unary)?
+    fn power<N: ExprNumber>(&mut self) -> ParseResult<N> {
+        let base: N = self.atom()?;
+        if self.peek() == Some(b'^') {
+            self.pos += 1;
+            self.descend()?;
+            let exponent = self.unary()?;
+            self.depth -= 1;
+            base.pow(exponent)
+        } else {
+            Ok(base)
+        }
+    }
+
+    // atom := number suffix? | '(' expr ')'
+    fn atom<N: ExprNumber>(&mut self) -> ParseResult<N> {
+        match self.peek() {
+            Some(b'(') => {
+                self.pos += 1;
+                self.descend()?;
+                let value = self.expr()?;
+                self.depth -= 1;
+                if self.peek() != Some(b')') {
+                    return Err(ParseError("missing closing parenthesis"));
+                }
+                self.pos += 1;
+                Ok(value)
+            }
+            Some(c) if c.is_ascii_digit() || c == b'.' => {
+                let start = self.pos;
+                while let Some(c) = self.input.get(self.pos) {
+                    if c.is_ascii_digit() || *c == b'.' || *c == b',' {
+                        self.pos += 1;
+                    } else {
+                        break;
+                    }
+                }
+                let digits: String = self.input[start..self.pos]
+                    .iter()
+                    .filter(|c| **c != b',')
+                    .map(|c| *c as char)
+                    .collect();
+                let scale = match self.input.get(self.pos).map(u8::to_ascii_lowercase) {
+                    Some(b'k') => 3,
+                    Some(b'm') => 6,
+                    Some(b'b') => 9,
+                    Some(b't') => 12,
+                    _ => 0,
+                };
+                if scale != 0 {
+                    self.pos += 1;
+                }
+                N::literal(&digits, scale)
+            }
+            _ => Err(ParseError("expected a number or `(`")),
+        }
+    }
+}
+
+fn evaluate<N: ExprNumber>(s: &str) -> ParseResult<N> {
+    let mut parser = ExprParser {
+        input: s.trim().as_bytes(),
+        pos: 0,
+        depth: 0,
+    };
+    let value = parser.expr()?;
+    if parser.peek().is_some() {
+        return Err(ParseError("unexpected character in expression"));
+    }
+    Ok(value)
+}
+
+/// Evaluates an arithmetic expression like `2*10^6`, `5k` or `(1.5m + 200) / 2`
+///
+/// Supports `+ - * / ^`, parentheses, and the suffixes `k`, `m`, `b` and `t`
+pub(crate) fn parse_math_expr(s: &str) -> ParseResult<f64> {
+    let value: f64 = evaluate(s)?;
+    if value.is_finite() {
+        Ok(value)
+    } else {
+        Err(TOO_LARGE)
+    }
+}
+
+/// Evaluates an arithmetic expression like `parse_math_expr`, requiring a whole number that fits
+/// in an `i64`
+///
+/// Evaluation is exact integer arithmetic, so every intermediate result must be whole too:
+/// `1.5k` is fine, but `7/2*2` is rejected
+pub(crate) fn parse_math_expr_int(s: &str) -> ParseResult<i64> {
+    let value: i128 = evaluate(s)?;
+    i64::try_from(value).map_err(|_| TOO_LARGE)
+}
+
+/// Parses user mentions and raw user IDs separated by spaces or commas, skipping duplicates
+///
+/// Errors if there isn't at least one user, so a mass action can't silently do nothing
+pub(crate) fn parse_user_list(s: &str) -> ParseResult<Vec<UserId>> {
+    let mut users = Vec::new();
+    for part in s.split(|c: char| c == ',' || c.is_whitespace()) {
+        if part.is_empty() {
+            continue;
+        }
+        let id = part
+            .strip_prefix("<@")
+            .and_then(|p| p.strip_suffix('>'))
+            .map(|p| p.trim_start_matches('!'))
+            .unwrap_or(part);
+        let id = UserId(
+            id.parse()
+                .map_err(|_| ParseError("expected user mentions or IDs"))?,
+        );
+        if !users.contains(&id) {
+            users.push(id);
+        }
+    }
+    if users.is_empty() {
+        return Err(ParseError("expected at least one user mention or ID"));
+    }
+    Ok(users)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytes_bounds() {
+        assert_eq!(parse_bytes("1.5 KiB").unwrap(), 1536);
+        assert_eq!(parse_bytes("10MB").unwrap(), 10_000_000);
+        assert!(parse_bytes("18446744073709551616").is_err());
+        assert!(parse_bytes("20000000TB").is_err());
+    }
+
+    #[test]
+    fn percent() {
+        assert_eq!(parse_percent("35%").unwrap(), 0.35);
+        assert_eq!(parse_percent("35").unwrap(), 0.35);
+        assert_eq!(parse_percent(" 50 % ").unwrap(), 0.5);
+        assert_eq!(parse_percent("0").unwrap(), 0.0);
+        assert_eq!(parse_percent("0.35").unwrap(), 0.35);
+        assert_eq!(parse_percent(".5").unwrap(), 0.5);
+        assert_eq!(parse_percent("1.0").unwrap(), 1.0);
+        assert_eq!(parse_percent("2.5%").unwrap(), 0.025);
+        assert!(parse_percent("35.5").is_err());
+        assert!(parse_percent("-0.5").is_err());
+        assert!(parse_percent("-5").is_err());
+        assert!(parse_percent("1e2").is_err());
+        assert_eq!(parse_percent("100%").unwrap(), 1.0);
+        assert!(parse_percent("-5%").is_err());
+        assert!(parse_percent("101%").is_err());
+        assert!(parse_percent("NaN").is_err());
+        assert!(parse_percent("%").is_err());
+    }
+
+    #[test]
+    fn user_list() {
+        let users = parse_user_list("<@1>, <@!2> 3,1").unwrap();
+        assert_eq!(users, vec![UserId(1), UserId(2), UserId(3)]);
+        assert!(parse_user_list("").is_err());
+        assert!(parse_user_list(" , ,").is_err());
+        assert!(parse_user_list("<@1> bob").is_err());
+    }
+
+    #[test]
+    fn math_expr_precedence() {
+        assert_eq!(parse_math_expr("1 + 2 * 3").unwrap(), 7.0);
+        assert_eq!(parse_math_expr("(1 + 2) * 3").unwrap(), 9.0);
+        assert_eq!(parse_math_expr("2 ^ 3 ^ 2").unwrap(), 512.0);
+        assert_eq!(parse_math_expr("-2 ^ 2").unwrap(), -4.0);
+        assert_eq!(parse_math_expr("10 - 4 - 3").unwrap(), 3.0);
+        assert_eq!(parse_math_expr("(1.5m + 200) / 2").unwrap(), 750_100.0);
+        assert_eq!(parse_math_expr_int("2*10^6").unwrap(), 2_000_000);
+        assert!(parse_math_expr("1 / 0").is_err());
+        assert!(parse_math_expr("1 +").is_err());
+        assert!(parse_math_expr("(1").is_err());
+        assert!(parse_math_expr("1 x").is_err());
+    }
+
+    #[test]
+    fn math_expr_suffixes() {
+        assert_eq!(parse_math_expr_int("5k").unwrap(), 5_000);
+        assert_eq!(parse_math_expr_int("1.5M").unwrap(), 1_500_000);
+        assert_eq!(parse_math_expr_int("2b").unwrap(), 2_000_000_000);
+        assert_eq!(parse_math_expr_int("3t").unwrap(), 3_000_000_000_000);
+        assert_eq!(parse_math_expr_int("1,000").unwrap(), 1_000);
+        assert_eq!(parse_math_expr_int("1.2345k").unwrap_err().0, NOT_WHOLE.0);
+        assert_eq!(parse_math_expr("0.5k").unwrap(), 500.0);
+    }
+
+    #[test]
+    fn math_expr_nesting_limit() {
+        let nested = |depth| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+        assert_eq!(parse_math_expr(&nested(MAX_EXPR_DEPTH)).unwrap(), 1.0);
+        assert!(parse_math_expr(&nested(MAX_EXPR_DEPTH + 1)).is_err());
+        assert!(parse_math_expr(&"-".repeat(10_000)).is_err());
+        assert!(parse_math_expr_int(&"2^".repeat(10_000)).is_err());
+    }
+
+    #[test]
+    fn math_expr_int_is_exact() {
+        assert_eq!(
+            parse_math_expr_int("9007199254740993").unwrap(),
+            9_007_199_254_740_993
+        );
+        assert_eq!(
+            parse_math_expr_int("123456789012345678").unwrap(),
+            123_456_789_012_345_678
+        );
+        assert_eq!(parse_math_expr_int("7/2").unwrap_err().0, NOT_WHOLE.0);
+        assert_eq!(parse_math_expr_int("2^-1").unwrap_err().0, NOT_WHOLE.0);
+        assert_eq!(parse_math_expr_int("10/2").unwrap(), 5);
+    }
+
+    #[test]
+    fn math_expr_int_bounds() {
+        assert_eq!(
+            parse_math_expr_int("9223372036854775807").unwrap(),
+            i64::MAX
+        );
+        assert_eq!(
+            parse_math_expr_int("-9223372036854775808").unwrap(),
+            i64::MIN
+        );
+        assert!(parse_math_expr_int("9223372036854775808").is_err());
+        assert!(parse_math_expr_int("-9223372036854775809").is_err());
+        assert_eq!(
+            parse_math_expr_int("(-2)^127/-1").unwrap_err().0,
+            TOO_LARGE.0
+        );
+        assert_eq!(
+            parse_math_expr_int("(-2)^127*-1").unwrap_err().0,
+            TOO_LARGE.0
+        );
+        assert_eq!(
+            parse_math_expr_int("-((-2)^127)").unwrap_err().0,
+            TOO_LARGE.0
+        );
+        assert!(parse_math_expr_int("9223372036854775807 + 1").is_err());
+        assert!(parse_math_expr_int("10^40").is_err());
+        assert!(parse_math_expr_int("10^100 - 10^100").is_err());
+    }
+
+    #[test]
+    fn message_link() {
+        let link = |guild_id| MessageLink {
+            guild_id,
+            channel_id: ChannelId(2),
+            message_id: MessageId(3),
+        };
+        let guild = link(Some(GuildId(1)));
+        assert_eq!(
+            parse_message_link("https://discord.com/channels/1/2/3").unwrap(),
+            guild
+        );
+        assert_eq!(
+            parse_message_link("<https://discordapp.com/channels/1/2/3>").unwrap(),
+            guild
+        );
+        assert_eq!(
+            parse_message_link("https://ptb.discord.com/channels/1/2/3").unwrap(),
+            guild
+        );
+        assert_eq!(
+            parse_message_link("https://canary.discord.com/channels/1/2/3").unwrap(),
+            guild
+        );
+        assert_eq!(
+            parse_message_link("https://discord.com/channels/1/2/3/").unwrap(),
+            guild
+        );
+        assert_eq!(
+            parse_message_link("https://discord.com/channels/@me/2/3").unwrap(),
+            link(None)
+        );
+        assert!(parse_message_link("https://discord.com/channels/1/2/3/4").is_err());
+        assert!(parse_message_link("https://discord.com/channels/1/2").is_err());
+        assert!(parse_message_link("https://discord.com/channels/1//3").is_err());
+        assert!(parse_message_link("discord.com/channels/1/2/3").is_err());
+        assert!(parse_message_link("https://example.com/channels/1/2/3").is_err());
+    }
+
+    #[test]
+    fn invite() {
+        assert_eq!(parse_invite("abc-123").unwrap(), "abc-123");
+        assert_eq!(parse_invite("discord.gg/x").unwrap(), "x");
+        assert_eq!(parse_invite("https://discord.gg/x/").unwrap(), "x");
+        assert_eq!(parse_invite("<https://discord.com/invite/x>").unwrap(), "x");
+        assert_eq!(parse_invite("http://discordapp.com/invite/x").unwrap(), "x");
+        assert!(parse_invite("discord.gg/").is_err());
+        assert!(parse_invite("https://example.com/x").is_err());
+        assert!(parse_invite("discord.gg/x/y").is_err());
+        assert!(parse_invite("").is_err());
+    }
+}
@@ -0,0 +1,102 @@
+use serenity::model::interactions::application_command::{
+    ApplicationCommandInteractionData, ApplicationCommandInteractionDataOption,
+    ApplicationCommandOptionType,
+};
+use std::fmt::{Display, Formatter};
+
+/// Full path of an invoked command, with segments separated by spaces (e.g. `config set`)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CommandPath(String);
+
+impl CommandPath {
+    pub(crate) fn new<'a>(segments: impl Iterator<Item = &'a str>) -> Self {
+        let mut path = String::new();
+        for segment in segments {
+            if !path.is_empty() {
+                path.push(' ');
+            }
+            path.push_str(segment);
+        }
+        Self(path)
+    }
+
+    /// Returns the path as a string, for matching against
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Iterates over the segments of the path, starting with the command name
+    pub fn iter(&self) -> std::str::Split<'_, char> {
+        self.0.split(' ')
+    }
+
+    /// Returns the number of segments in the path, including the command name
+    pub fn depth(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Returns the path without its last segment, if it has more than one
+    pub fn parent(&self) -> Option<&str> {
+        self.0.rfind(' ').map(|i| &self.0[..i])
+    }
+
+    /// Returns the last segment of the path
+    pub fn leaf(&self) -> &str {
+        match self.0.rfind(' ') {
+            Some(i) => &self.0[i + 1..],
+            None => &self.0,
+        }
+    }
+
+    /// Checks if the path is `prefix` or a subcommand of it
+    pub fn starts_with(&self, prefix: &str) -> bool {
+        match self.0.strip_prefix(prefix) {
+            Some(rest) => rest.is_empty() || rest.starts_with(' '),
+            None => false,
+        }
+    }
+}
+
+impl Display for CommandPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Iterator over the segments of an interaction's command path, borrowed from the interaction
+///
+/// Unlike `CommandPath`, this doesn't allocate, which suits routers handling a high volume of
+/// interactions
+pub struct PathSegments<'a> {
+    name: Option<&'a str>,
+    pub(crate) options: &'a [ApplicationCommandInteractionDataOption],
+}
+
+impl<'a> Iterator for PathSegments<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        if let Some(name) = self.name.take() {
+            return Some(name);
+        }
+        let option = self.options.first()?;
+        if matches!(
+            option.kind,
+            ApplicationCommandOptionType::SubCommand
+                | ApplicationCommandOptionType::SubCommandGroup
+        ) {
+            self.options = &option.options;
+            Some(&option.name)
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns the segments of the command path of a `ApplicationCommandInteractionData`
+pub fn path_segments(interaction: &ApplicationCommandInteractionData) -> PathSegments<'_> {
+    PathSegments {
+        name: Some(&interaction.name),
+        options: &interaction.options,
+    }
+}
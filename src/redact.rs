@@ -0,0 +1,30 @@
+use crate::SlashMap;
+use std::fmt::{Debug, Formatter};
+
+/// Debug view of a `SlashMap` that masks the values of some arguments
+///
+/// Created by `SlashMap::redacted`
+pub struct Redacted<'a> {
+    map: &'a SlashMap,
+    names: &'a [&'a str],
+}
+
+impl<'a> Redacted<'a> {
+    pub(crate) fn new(map: &'a SlashMap, names: &'a [&'a str]) -> Self {
+        Self { map, names }
+    }
+}
+
+impl Debug for Redacted<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut map = f.debug_map();
+        for (name, value) in &self.map.0 {
+            if self.names.contains(&name.as_str()) {
+                map.entry(name, &format_args!("<redacted>"));
+            } else {
+                map.entry(name, value);
+            }
+        }
+        map.finish()
+    }
+}
@@ -0,0 +1,96 @@
+use chrono::{DateTime, Duration, FixedOffset, NaiveDateTime, NaiveTime, Offset, TimeZone, Utc};
+
+/// Supplies the timezone a user gives times in, e.g. from per-user or per-guild settings
+///
+/// Implemented for every chrono `TimeZone`, so `FixedOffset` or a `chrono_tz::Tz` can be passed
+/// directly
+pub trait TimezoneProvider {
+    /// Returns the UTC offset in effect at the local date and time `at`, which may be on the
+    /// other side of a daylight saving change from now
+    fn utc_offset(&self, at: NaiveDateTime) -> FixedOffset;
+}
+
+impl<Tz: TimeZone> TimezoneProvider for Tz {
+    fn utc_offset(&self, at: NaiveDateTime) -> FixedOffset {
+        match self.offset_from_local_datetime(&at).earliest() {
+            Some(offset) => offset.fix(),
+            // `at` was skipped by a change, e.g. 2:30am when clocks jump to 3am
+            None => self.offset_from_utc_datetime(&at).fix(),
+        }
+    }
+}
+
+/// Returns the first time after `now` that the clock in `tz` shows `time`
+pub(crate) fn next_time_of_day(
+    time: NaiveTime,
+    now: DateTime<Utc>,
+    tz: &impl TimezoneProvider,
+) -> Option<DateTime<FixedOffset>> {
+    // the local date can be a day either side of the UTC one, and the next occurrence a day
+    // after that
+    let today = now.naive_utc().date();
+    (-1..=2)
+        .filter_map(|days| {
+            let local = (today + Duration::days(days)).and_time(time);
+            tz.utc_offset(local).from_local_datetime(&local).single()
+        })
+        .filter(|candidate| *candidate > now)
+        .min()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn naive(d: u32, h: u32, m: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2021, 3, d)
+            .and_then(|date| date.and_hms_opt(h, m, 0))
+            .unwrap()
+    }
+
+    fn utc(d: u32, h: u32, m: u32) -> DateTime<Utc> {
+        Utc.from_utc_datetime(&naive(d, h, m))
+    }
+
+    fn offset(hours: i32) -> FixedOffset {
+        FixedOffset::east_opt(hours * 3600).unwrap()
+    }
+
+    fn time(h: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, 0, 0).unwrap()
+    }
+
+    /// UTC+0 until 2021-03-28 01:00 local time, then UTC+1
+    struct London;
+
+    impl TimezoneProvider for London {
+        fn utc_offset(&self, at: NaiveDateTime) -> FixedOffset {
+            offset(if at < naive(28, 1, 0) { 0 } else { 1 })
+        }
+    }
+
+    #[test]
+    fn fixed_offset() {
+        let next = next_time_of_day(time(18), utc(10, 12, 0), &offset(2)).unwrap();
+        assert_eq!(next, utc(10, 16, 0));
+        let next = next_time_of_day(time(18), utc(10, 16, 0), &offset(2)).unwrap();
+        assert_eq!(next, utc(11, 16, 0));
+    }
+
+    #[test]
+    fn across_dst_change() {
+        // asked the evening before clocks go forward, for 6pm the next day
+        let next = next_time_of_day(time(18), utc(27, 20, 0), &London).unwrap();
+        assert_eq!(next, utc(28, 17, 0));
+        assert_eq!(next.offset().local_minus_utc(), 3600);
+    }
+
+    #[test]
+    fn large_offsets() {
+        let next = next_time_of_day(time(9), utc(10, 1, 0), &offset(-11)).unwrap();
+        assert_eq!(next, utc(10, 20, 0));
+        let next = next_time_of_day(time(9), utc(10, 23, 0), &offset(14)).unwrap();
+        assert_eq!(next, utc(11, 19, 0));
+    }
+}
@@ -0,0 +1,67 @@
+#![cfg(feature = "derive")]
+
+use serenity::model::interactions::application_command::ApplicationCommandInteractionDataOptionValue as Value;
+use serenity_slash_decode::{process_json, Error, FromSlashMap, SlashMap};
+
+#[derive(FromSlashMap)]
+struct Args {
+    text: String,
+    count: Option<i64>,
+    #[slash(rename = "dry-run")]
+    dry_run: bool,
+}
+
+#[derive(FromSlashMap)]
+struct NoArgs {}
+
+fn map(values: Vec<(&str, Value)>) -> SlashMap {
+    let (_, mut map) = process_json(br#"{"id":"1","name":"cmd"}"#).unwrap();
+    for (name, value) in values {
+        map.insert(name, value);
+    }
+    map
+}
+
+#[test]
+fn reads_required_and_optional_fields() {
+    let args = Args::from_slash_map(map(vec![
+        ("text", Value::String("hi".to_string())),
+        ("count", Value::Integer(3)),
+        ("dry-run", Value::Boolean(true)),
+    ]))
+    .unwrap();
+    assert_eq!(args.text, "hi");
+    assert_eq!(args.count, Some(3));
+    assert!(args.dry_run);
+}
+
+#[test]
+fn missing_optional_field_is_none() {
+    let args = Args::from_slash_map(map(vec![
+        ("text", Value::String("hi".to_string())),
+        ("dry-run", Value::Boolean(false)),
+    ]))
+    .unwrap();
+    assert_eq!(args.count, None);
+}
+
+#[test]
+fn missing_required_field_errors() {
+    let result = Args::from_slash_map(map(vec![("text", Value::String("hi".to_string()))]));
+    assert!(matches!(result, Err(Error::MissingValue { name }) if name == "dry-run"));
+}
+
+#[test]
+fn wrong_type_in_optional_field_errors() {
+    let result = Args::from_slash_map(map(vec![
+        ("text", Value::String("hi".to_string())),
+        ("count", Value::Boolean(true)),
+        ("dry-run", Value::Boolean(false)),
+    ]));
+    assert!(matches!(result, Err(Error::WrongType { name, .. }) if name == "count"));
+}
+
+#[test]
+fn struct_without_fields() {
+    assert!(NoArgs::from_slash_map(map(vec![])).is_ok());
+}